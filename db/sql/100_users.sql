@@ -11,5 +11,9 @@ create table users (
     totp_algorithm smallint,
     totp_secret varchar,
     totp_step smallint,
-    totp_digits smallint
+    totp_digits smallint,
+
+    role smallint not null default 1,
+
+    quota bigint
 )
\ No newline at end of file
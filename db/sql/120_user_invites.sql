@@ -0,0 +1,16 @@
+create table user_invites (
+    id varchar not null primary key,
+
+    issued_by bigint not null,
+    email varchar,
+    role smallint not null default 1,
+
+    issued timestamp with time zone not null,
+    expires timestamp with time zone not null,
+
+    used_by bigint,
+    used_on timestamp with time zone,
+
+    constraint issued_by_fk foreign key (issued_by) references users (id),
+    constraint used_by_fk foreign key (used_by) references users (id)
+)
\ No newline at end of file
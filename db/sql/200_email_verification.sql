@@ -1,5 +1,5 @@
 create table email_verificiations (
-    users_id integer not null primary key,
+    users_id bigint not null primary key,
     key_id varchar not null unique,
     issued timestamp with time zone not null,
 
@@ -12,7 +12,13 @@ create table fs_items (
     created timestamp with time zone not null,
     modified timestamp with time zone,
 
+    -- sha256 of the contents. only filled in by a deep sync and cleared when
+    -- the contents may have changed
+    checksum bytea,
+
     item_exists boolean default true,
+    -- when the item was last found to be missing from storage
+    missing_since timestamp with time zone,
 
     user_data json not null,
 
@@ -20,4 +26,6 @@ create table fs_items (
 
     constraint users_id_fk foreign key (users_id) references users (id),
     constraint perent_fk foreign key (parent) references fs_items (id)
-)
\ No newline at end of file
+);
+
+create unique index fs_items_path_idx on fs_items (users_id, directory, basename);
\ No newline at end of file
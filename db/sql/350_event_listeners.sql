@@ -1,8 +1,16 @@
 create table event_listeners (
     id uuid primary key not null,
     
-    event_name varchar not null,
+    event_name varchar[] not null,
     endpoint varchar not null,
+    secret varchar not null,
+
+    -- filters applied to fs_items before a delivery is created
+    recursive boolean not null default true,
+    basename_pattern varchar,
+    item_type smallint,
+    min_size bigint,
+    max_size bigint,
 
     ref_table varchar not null,
     ref_id bigint not null,
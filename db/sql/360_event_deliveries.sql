@@ -0,0 +1,22 @@
+create table event_deliveries (
+    id uuid primary key not null,
+
+    listener_id uuid,
+    endpoint varchar not null,
+    event_name varchar not null,
+    payload varchar not null,
+
+    status smallint not null default 1,
+    attempts smallint not null default 0,
+    next_attempt timestamp with time zone not null,
+    last_attempt timestamp with time zone,
+    last_status smallint,
+    last_error varchar,
+
+    created timestamp with time zone not null,
+
+    users_id bigint not null,
+
+    constraint listener_id_fk foreign key (listener_id) references event_listeners (id) on delete set null,
+    constraint users_id_fk foreign key (users_id) references users (id)
+)
\ No newline at end of file
@@ -0,0 +1,22 @@
+create table audit_log (
+    id uuid primary key not null,
+
+    created timestamp with time zone not null,
+
+    -- not a foreign key since entries need to outlive the user. will be null
+    -- for actions without a known user like a failed login
+    users_id bigint,
+
+    action varchar not null,
+
+    ip inet,
+    user_agent varchar,
+
+    target_type varchar,
+    target_ids varchar[] not null default '{}',
+
+    details jsonb not null default '{}'
+);
+
+create index audit_log_created_idx on audit_log (created);
+create index audit_log_users_id_idx on audit_log (users_id, created);
\ No newline at end of file
@@ -0,0 +1,62 @@
+create table fs_item_changes (
+    id bigserial primary key not null,
+
+    -- position in the change feed. assigned after the change is committed so
+    -- that a transaction finishing late cannot land behind a cursor a client
+    -- has already moved past
+    seq bigint,
+
+    -- not a foreign key since deletes need to be kept as tombstones
+    fs_items_id bigint not null,
+    users_id bigint not null,
+
+    change_type varchar not null,
+    item_type smallint not null,
+
+    directory varchar not null,
+    basename varchar not null,
+
+    -- location before a move
+    prev_directory varchar,
+    prev_basename varchar,
+
+    created timestamp with time zone not null default now()
+);
+
+create sequence fs_item_changes_seq;
+
+create unique index fs_item_changes_seq_idx on fs_item_changes (seq);
+create index fs_item_changes_users_id_idx on fs_item_changes (users_id, seq);
+create index fs_item_changes_pending_idx on fs_item_changes (id) where seq is null;
+
+create function record_fs_item_change() returns trigger as $$
+begin
+    if tg_op = 'INSERT' then
+        insert into fs_item_changes (fs_items_id, users_id, change_type, item_type, directory, basename)
+        values (new.id, new.users_id, 'created', new.item_type, new.directory, new.basename);
+    elsif tg_op = 'DELETE' then
+        insert into fs_item_changes (fs_items_id, users_id, change_type, item_type, directory, basename)
+        values (old.id, old.users_id, 'deleted', old.item_type, old.directory, old.basename);
+    elsif new.users_id <> old.users_id then
+        -- the previous owner will no longer see the item
+        insert into fs_item_changes (fs_items_id, users_id, change_type, item_type, directory, basename)
+        values (old.id, old.users_id, 'deleted', old.item_type, old.directory, old.basename);
+        insert into fs_item_changes (fs_items_id, users_id, change_type, item_type, directory, basename)
+        values (new.id, new.users_id, 'created', new.item_type, new.directory, new.basename);
+    elsif new.directory <> old.directory or new.basename <> old.basename then
+        insert into fs_item_changes (fs_items_id, users_id, change_type, item_type, directory, basename, prev_directory, prev_basename)
+        values (new.id, new.users_id, 'moved', new.item_type, new.directory, new.basename, old.directory, old.basename);
+    elsif (new.item_type, new.parent, new.item_size, new.created, new.modified, new.item_exists, new.user_data::text)
+          is distinct from
+          (old.item_type, old.parent, old.item_size, old.created, old.modified, old.item_exists, old.user_data::text) then
+        insert into fs_item_changes (fs_items_id, users_id, change_type, item_type, directory, basename)
+        values (new.id, new.users_id, 'updated', new.item_type, new.directory, new.basename);
+    end if;
+
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger fs_item_changes_trigger
+after insert or update or delete on fs_items
+for each row execute procedure record_fs_item_change();
\ No newline at end of file
@@ -0,0 +1,36 @@
+-- data fix for databases created before directories were stored without a
+-- leading slash. the old sync wrote "/123/a" where everything else now
+-- writes "123/a", so a sync after the change inserts a second row for the
+-- same item. the newer rows are dropped in favor of the old ones, which
+-- keep their ids, listeners, and user data, and the old rows are trimmed.
+begin;
+
+create temporary table fs_items_twins on commit drop as
+select new_item.id as new_id,
+       old_item.id as old_id
+from fs_items new_item
+join fs_items old_item on old_item.users_id = new_item.users_id and
+                          old_item.directory = '/' || new_item.directory and
+                          old_item.basename = new_item.basename;
+
+update fs_items
+set parent = fs_items_twins.old_id
+from fs_items_twins
+where fs_items.parent = fs_items_twins.new_id;
+
+update event_listeners
+set ref_id = fs_items_twins.old_id
+from fs_items_twins
+where event_listeners.ref_table = 'fs_items' and
+      event_listeners.ref_id = fs_items_twins.new_id;
+
+delete from fs_items
+where id in (select new_id from fs_items_twins);
+
+update fs_items
+set directory = ltrim(directory, '/')
+where directory like '/%';
+
+create unique index if not exists fs_items_path_idx on fs_items (users_id, directory, basename);
+
+commit;
\ No newline at end of file
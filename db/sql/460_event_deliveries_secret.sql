@@ -0,0 +1,4 @@
+-- deliveries are signed with the current secret of their listener when they
+-- are sent so the copy kept in each row is removed. deliveries left without a
+-- listener are dead lettered by the delivery worker
+alter table event_deliveries drop column if exists secret;
\ No newline at end of file
@@ -0,0 +1,63 @@
+-- brings databases created from older versions of the create scripts up to
+-- date. everything here is a no-op for a database created from the current
+-- scripts
+begin;
+
+alter table email_verificiations alter column users_id type bigint;
+
+alter table users add column if not exists quota bigint;
+
+do $$
+begin
+    if not exists (
+        select 1
+        from information_schema.columns
+        where table_name = 'users' and
+              column_name = 'role'
+    ) then
+        alter table users add column role smallint not null default 1;
+
+        -- user management needs an administrator. the first user created
+        -- would have been given the role if it had existed
+        update users
+        set role = 2
+        where id = (select min(id) from users);
+    end if;
+end
+$$;
+
+alter table fs_items add column if not exists checksum bytea;
+alter table fs_items add column if not exists missing_since timestamp with time zone;
+
+do $$
+begin
+    if exists (
+        select 1
+        from information_schema.columns
+        where table_name = 'event_listeners' and
+              column_name = 'event_name' and
+              data_type <> 'ARRAY'
+    ) then
+        alter table event_listeners
+            alter column event_name type varchar[] using array[event_name];
+    end if;
+end
+$$;
+
+-- listeners from before signing get a random secret. it can be replaced by
+-- rotating the secret of the listener
+alter table event_listeners add column if not exists secret varchar;
+
+update event_listeners
+set secret = replace(gen_random_uuid()::text, '-', '') || replace(gen_random_uuid()::text, '-', '')
+where secret is null;
+
+alter table event_listeners alter column secret set not null;
+
+alter table event_listeners add column if not exists recursive boolean not null default true;
+alter table event_listeners add column if not exists basename_pattern varchar;
+alter table event_listeners add column if not exists item_type smallint;
+alter table event_listeners add column if not exists min_size bigint;
+alter table event_listeners add column if not exists max_size bigint;
+
+commit;
\ No newline at end of file
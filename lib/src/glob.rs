@@ -0,0 +1,224 @@
+// simple glob patterns for matching a single path segment. supports `*`,
+// `?`, character classes `[abc]`, ranges `[a-z]`, negation `[!abc]` and
+// escaping with `\`
+
+#[derive(Debug, PartialEq)]
+pub enum PatternError {
+    UnclosedClass,
+    EmptyClass,
+    InvalidRange,
+    TrailingEscape
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::UnclosedClass => write!(f, "character class is missing a closing bracket"),
+            PatternError::EmptyClass => write!(f, "character class is empty"),
+            PatternError::InvalidRange => write!(f, "character range start is greater than the end"),
+            PatternError::TrailingEscape => write!(f, "pattern ends with an escape character")
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Char(char),
+    Any,
+    AnyMany,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>
+    }
+}
+
+impl Token {
+    fn matches(&self, ch: char) -> bool {
+        match self {
+            Token::Char(c) => *c == ch,
+            Token::Any => true,
+            Token::AnyMany => true,
+            Token::Class { negated, ranges } => {
+                let found = ranges.iter().any(|(start, end)| *start <= ch && ch <= *end);
+
+                found != *negated
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    tokens: Vec<Token>
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Pattern, PatternError> {
+        let mut tokens = Vec::with_capacity(pattern.len());
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '*' => {
+                    // multiple stars behave the same as one
+                    if !matches!(tokens.last(), Some(Token::AnyMany)) {
+                        tokens.push(Token::AnyMany);
+                    }
+                },
+                '?' => tokens.push(Token::Any),
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        tokens.push(Token::Char(escaped));
+                    } else {
+                        return Err(PatternError::TrailingEscape);
+                    }
+                },
+                '[' => {
+                    let mut negated = false;
+                    let mut ranges = Vec::new();
+                    let mut closed = false;
+
+                    if chars.peek() == Some(&'!') {
+                        chars.next();
+                        negated = true;
+                    }
+
+                    while let Some(start) = chars.next() {
+                        if start == ']' {
+                            closed = true;
+                            break;
+                        }
+
+                        let start = if start == '\\' {
+                            chars.next().ok_or(PatternError::TrailingEscape)?
+                        } else {
+                            start
+                        };
+
+                        if chars.peek() == Some(&'-') {
+                            chars.next();
+
+                            match chars.next() {
+                                Some(']') => {
+                                    // a trailing dash is treated as a literal
+                                    ranges.push((start, start));
+                                    ranges.push(('-', '-'));
+                                    closed = true;
+                                    break;
+                                },
+                                Some(end) => {
+                                    if start > end {
+                                        return Err(PatternError::InvalidRange);
+                                    }
+
+                                    ranges.push((start, end));
+                                },
+                                None => return Err(PatternError::UnclosedClass)
+                            }
+                        } else {
+                            ranges.push((start, start));
+                        }
+                    }
+
+                    if !closed {
+                        return Err(PatternError::UnclosedClass);
+                    }
+
+                    if ranges.is_empty() {
+                        return Err(PatternError::EmptyClass);
+                    }
+
+                    tokens.push(Token::Class { negated, ranges });
+                },
+                _ => tokens.push(Token::Char(ch))
+            }
+        }
+
+        Ok(Pattern { tokens })
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        let chars: Vec<char> = value.chars().collect();
+        let mut t = 0;
+        let mut c = 0;
+
+        // the last star seen and the position in the value it was matched at
+        // so that we can backtrack if the remaining tokens fail
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while c < chars.len() {
+            if t < self.tokens.len() {
+                if let Token::AnyMany = self.tokens[t] {
+                    backtrack = Some((t, c));
+                    t += 1;
+                    continue;
+                }
+
+                if self.tokens[t].matches(chars[c]) {
+                    t += 1;
+                    c += 1;
+                    continue;
+                }
+            }
+
+            if let Some((star_t, star_c)) = backtrack {
+                t = star_t + 1;
+                c = star_c + 1;
+                backtrack = Some((star_t, star_c + 1));
+            } else {
+                return false;
+            }
+        }
+
+        self.tokens[t..].iter().all(|token| matches!(token, Token::AnyMany))
+    }
+}
+
+pub fn matches(pattern: &str, value: &str) -> Result<bool, PatternError> {
+    Ok(Pattern::new(pattern)?.matches(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(pattern: &str, value: &str) -> bool {
+        matches(pattern, value).unwrap()
+    }
+
+    #[test]
+    fn literal_and_wildcards() {
+        assert!(check("report.txt", "report.txt"));
+        assert!(!check("report.txt", "report.txts"));
+        assert!(check("*.txt", "report.txt"));
+        assert!(check("*.txt", ".txt"));
+        assert!(!check("*.txt", "report.txt.bak"));
+        assert!(check("r?port.*", "report.md"));
+        assert!(!check("r?port", "rport"));
+        assert!(check("*", ""));
+        assert!(check("a*b*c", "aXXbYYbc"));
+        assert!(!check("a*b*c", "aXXbYYb"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(check("img_[0-9][0-9].png", "img_07.png"));
+        assert!(!check("img_[0-9][0-9].png", "img_7a.png"));
+        assert!(check("[!.]*", "visible"));
+        assert!(!check("[!.]*", ".hidden"));
+        assert!(check("file[-_]a", "file-a"));
+        assert!(check("file[_-]a", "file-a"));
+        assert!(check("\\*.txt", "*.txt"));
+        assert!(!check("\\*.txt", "a.txt"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        assert_eq!(Pattern::new("[abc").unwrap_err(), PatternError::UnclosedClass);
+        assert_eq!(Pattern::new("[]").unwrap_err(), PatternError::EmptyClass);
+        assert_eq!(Pattern::new("[z-a]").unwrap_err(), PatternError::InvalidRange);
+        assert_eq!(Pattern::new("abc\\").unwrap_err(), PatternError::TrailingEscape);
+    }
+}
\ No newline at end of file
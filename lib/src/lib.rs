@@ -2,6 +2,8 @@ pub mod units;
 pub mod time;
 pub mod string;
 pub mod snowflake;
+pub mod webhook;
+pub mod glob;
 
 #[cfg(test)]
 mod tests {
@@ -104,14 +104,14 @@ impl TokioSnowflake {
                 }
 
                 *sequence_lock += 1;
-                seq_value = sequence_lock.clone();
+                seq_value = *sequence_lock;
             } else {
                 *prev_time_lock = now;
                 *sequence_lock = 1;
             }
         }
 
-        Ok((now << SNOWFLAKE_MACHINE_ID_BITS + SNOWFLAKE_SEQUENCE_ID_BITS) |
+        Ok((now << (SNOWFLAKE_MACHINE_ID_BITS + SNOWFLAKE_SEQUENCE_ID_BITS)) |
             (self.machine_id << SNOWFLAKE_SEQUENCE_ID_BITS) |
             seq_value)
     }
@@ -151,7 +151,7 @@ fn current_timestamp_millis() -> i64 {
 }
 
 pub fn decompose(value: i64) -> (i64,i64,i64) {
-    return (
+    (
         (value & SNOWFLAKE_TIMESTAMP_BIT_MASK) >> (SNOWFLAKE_MACHINE_ID_BITS + SNOWFLAKE_SEQUENCE_ID_BITS),
         (value & SNOWFLAKE_MACHINE_ID_BIT_MASK) >> SNOWFLAKE_SEQUENCE_ID_BITS,
         value & SNOWFLAKE_SEQUENCE_ID_BIT_MASK
@@ -1,12 +1,8 @@
-use std::path::PathBuf;
+use std::path::Path;
 
-pub fn name_from_pathbuf(path: &PathBuf) -> Option<String> {
+pub fn name_from_pathbuf(path: &Path) -> Option<String> {
     if let Some(name) = path.file_name() {
-        if let Ok(rtn) = name.to_os_string().into_string() {
-            Some(rtn)
-        } else {
-            None
-        }
+        name.to_os_string().into_string().ok()
     } else {
         None
     }
@@ -38,7 +34,7 @@ where
         }
     }
 
-    while let Some(ch) = chars.next() {
+    for ch in chars {
         if ch == '/' {
             let remaining = directory.capacity() - directory.len();
 
@@ -46,8 +42,14 @@ where
                 directory.reserve(working.len() + 1);
             }
 
-            directory.push('/');
-            directory.push_str(&working);
+            // only the first segment can be without a separator when no
+            // leading slash is requested
+            if no_leading && directory.is_empty() {
+                directory.push_str(&working);
+            } else {
+                directory.push('/');
+                directory.push_str(&working);
+            }
             working.clear();
         } else {
             if working.capacity() == working.len() {
@@ -62,4 +64,16 @@ where
     working.shrink_to_fit();
 
     (directory, working)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_and_basename() {
+        assert_eq!(get_directory_and_basename("123", true), ("".to_owned(), "123".to_owned()));
+        assert_eq!(get_directory_and_basename("123/a", true), ("123".to_owned(), "a".to_owned()));
+        assert_eq!(get_directory_and_basename("/123/a/b.txt", true), ("123/a".to_owned(), "b.txt".to_owned()));
+    }
 }
\ No newline at end of file
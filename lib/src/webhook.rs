@@ -0,0 +1,141 @@
+use ring::hmac;
+
+// headers sent along with every webhook request
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+#[derive(Debug, PartialEq)]
+pub enum VerifyError {
+    InvalidFormat,
+    InvalidSignature,
+    Expired
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyError::InvalidFormat => write!(f, "signature is not in the expected format"),
+            VerifyError::InvalidSignature => write!(f, "signature does not match the given body"),
+            VerifyError::Expired => write!(f, "timestamp is outside of the allowed tolerance")
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+fn signed_data(timestamp: i64, body: &[u8]) -> Vec<u8> {
+    let timestamp_str = timestamp.to_string();
+    let mut data = Vec::with_capacity(timestamp_str.len() + 1 + body.len());
+    data.extend_from_slice(timestamp_str.as_bytes());
+    data.push(b'.');
+    data.extend_from_slice(body);
+    data
+}
+
+fn decode_hex(given: &str) -> Option<Vec<u8>> {
+    if given.len() % 2 == 1 {
+        return None;
+    }
+
+    let mut rtn = Vec::with_capacity(given.len() / 2);
+    let bytes = given.as_bytes();
+
+    for pair in bytes.chunks(2) {
+        let high = (pair[0] as char).to_digit(16)?;
+        let low = (pair[1] as char).to_digit(16)?;
+
+        rtn.push((high << 4 | low) as u8);
+    }
+
+    Some(rtn)
+}
+
+/// creates the signature for a webhook body. the signed data is the unix
+/// timestamp in seconds and the body joined by a "."
+pub fn sign(secret: &[u8], timestamp: i64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    let tag = hmac::sign(&key, &signed_data(timestamp, body));
+    let mut rtn = String::with_capacity(SIGNATURE_PREFIX.len() + tag.as_ref().len() * 2);
+    rtn.push_str(SIGNATURE_PREFIX);
+
+    for byte in tag.as_ref() {
+        rtn.push_str(&format!("{:02x}", byte));
+    }
+
+    rtn
+}
+
+/// checks the signature against the timestamp and body. the comparison is
+/// done in constant time
+pub fn verify(secret: &[u8], timestamp: i64, body: &[u8], signature: &str) -> Result<(), VerifyError> {
+    let hex = signature.strip_prefix(SIGNATURE_PREFIX)
+        .ok_or(VerifyError::InvalidFormat)?;
+    let tag = decode_hex(hex).ok_or(VerifyError::InvalidFormat)?;
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+
+    hmac::verify(&key, &signed_data(timestamp, body), &tag)
+        .map_err(|_| VerifyError::InvalidSignature)
+}
+
+/// same as verify but will also reject timestamps that are more than
+/// tolerance seconds away from now to help prevent replays
+pub fn verify_with_tolerance(
+    secret: &[u8],
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+    tolerance: i64
+) -> Result<(), VerifyError> {
+    if (now - timestamp).abs() > tolerance {
+        return Err(VerifyError::Expired);
+    }
+
+    verify(secret, timestamp, body, signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test_secret";
+    const BODY: &[u8] = b"{\"event\":\"fs_item:created\"}";
+
+    #[test]
+    fn sign_and_verify() {
+        let signature = sign(SECRET, 1600000000, BODY);
+
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), 7 + 64);
+        assert_eq!(verify(SECRET, 1600000000, BODY, &signature), Ok(()));
+    }
+
+    #[test]
+    fn rejects_modified() {
+        let signature = sign(SECRET, 1600000000, BODY);
+
+        assert_eq!(verify(SECRET, 1600000001, BODY, &signature), Err(VerifyError::InvalidSignature));
+        assert_eq!(verify(SECRET, 1600000000, b"{}", &signature), Err(VerifyError::InvalidSignature));
+        assert_eq!(verify(b"other", 1600000000, BODY, &signature), Err(VerifyError::InvalidSignature));
+    }
+
+    #[test]
+    fn rejects_invalid_format() {
+        assert_eq!(verify(SECRET, 0, BODY, "abcd"), Err(VerifyError::InvalidFormat));
+        assert_eq!(verify(SECRET, 0, BODY, "sha256=zz"), Err(VerifyError::InvalidFormat));
+        assert_eq!(verify(SECRET, 0, BODY, "sha256=abc"), Err(VerifyError::InvalidFormat));
+    }
+
+    #[test]
+    fn tolerance() {
+        let signature = sign(SECRET, 1600000000, BODY);
+
+        assert_eq!(verify_with_tolerance(SECRET, 1600000000, BODY, &signature, 1600000100, 300), Ok(()));
+        assert_eq!(
+            verify_with_tolerance(SECRET, 1600000000, BODY, &signature, 1600000301, 300),
+            Err(VerifyError::Expired)
+        );
+    }
+}
\ No newline at end of file
@@ -6,7 +6,7 @@ pub const DEFAULT_DIGITS: u32 = 8;
 fn hmac_one_off(algorithm: Algorithm, secret: &[u8], data: &[u8]) -> Tag {
     let key = hmac::Key::new(algorithm, secret);
     let mut context = hmac::Context::with_key(&key);
-    context.update(&data);
+    context.update(data);
     context.sign()
 }
 
@@ -82,16 +82,16 @@ mod tests {
             "520489",
         ];
 
-        for count in 0..results.len() {
+        for (count, expected) in results.iter().enumerate() {
             let check = htop(secret, 6, count as u64);
 
             assert_eq!(
                 check.as_str(), 
-                results[count], 
+                *expected, 
                 "count: {} received: {} expected: {}", 
                 count, 
                 check, 
-                results[count]
+                expected
             );
         }
     }
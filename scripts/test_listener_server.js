@@ -1,4 +1,5 @@
 const http = require("http");
+const crypto = require("crypto");
 const JSONBig = require("json-bigint");
 
 const JSONLocal = JSONBig({strict: true, useNativeBigInt: true});
@@ -27,6 +28,16 @@ let server = http.createServer(async (req, res) => {
     console.log(req.method, req.url);
 
     let body = await readBody(req);
+
+    if (process.env.WEBHOOK_SECRET) {
+        let timestamp = req.headers["x-webhook-timestamp"];
+        let signature = req.headers["x-webhook-signature"];
+        let expected = "sha256=" + crypto.createHmac("sha256", process.env.WEBHOOK_SECRET)
+            .update(`${timestamp}.${body}`)
+            .digest("hex");
+
+        console.log("signature valid:", signature === expected);
+    }
     let json = JSONLocal.parse(body);
 
     console.log(json);
@@ -0,0 +1,51 @@
+use std::net::IpAddr;
+
+use hyper::{HeaderMap, http::Extensions};
+
+use crate::db::record::AuditEntry;
+
+pub mod action {
+    pub const LOGIN: &str = "auth:login";
+    pub const LOGIN_FAILED: &str = "auth:login_failed";
+    pub const LOGOUT: &str = "auth:logout";
+    pub const PASSWORD_CHANGED: &str = "auth:password_changed";
+    pub const SESSION_REVOKED: &str = "session:revoked";
+    pub const FS_ITEM_CREATED: &str = "fs_item:created";
+    pub const FS_ITEM_UPDATED: &str = "fs_item:updated";
+    pub const FS_ITEM_DELETED: &str = "fs_item:deleted";
+    pub const FS_ITEM_SYNCED: &str = "fs_item:synced";
+    pub const LISTENER_CREATED: &str = "listener:created";
+    pub const LISTENER_UPDATED: &str = "listener:updated";
+    pub const LISTENER_DELETED: &str = "listener:deleted";
+    pub const LISTENER_ROTATED: &str = "listener:rotated";
+    pub const USER_UPDATED: &str = "user:updated";
+    pub const USER_DELETED: &str = "user:deleted";
+}
+
+// information about the client making the request. the ip is the one
+// inserted by the router which will already account for x-forwarded-for
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>
+}
+
+impl AuditContext {
+    pub fn new(headers: &HeaderMap, extensions: &Extensions) -> Self {
+        let user_agent = headers.get("user-agent")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_owned());
+
+        AuditContext {
+            ip: extensions.get::<IpAddr>().cloned(),
+            user_agent
+        }
+    }
+
+    pub fn entry(&self, users_id: Option<i64>, action: &str) -> AuditEntry {
+        let mut entry = AuditEntry::new(users_id, action);
+        entry.ip = self.ip;
+        entry.user_agent = self.user_agent.clone();
+        entry
+    }
+}
\ No newline at end of file
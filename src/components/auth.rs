@@ -1,7 +1,7 @@
 use std::option::Option;
 
 use chrono::Utc;
-use hyper::{HeaderMap, Uri};
+use hyper::{HeaderMap, Uri, http::Extensions};
 use tokio_postgres::GenericClient;
 use ring::hmac;
 use lib::time::unix_epoch_sec_now;
@@ -12,104 +12,29 @@ use crate::{
     db::record::{User, UserSession, TotpAlgorithm}
 };
 
-/*
-pub struct SessionTuple (User, UserSession);
+// set in the request extensions by the auth layer for any route that looks
+// up the session
+pub struct SessionTuple(pub User, pub UserSession);
 
-impl SessionTuple {
-
-    pub fn try_from(req: &mut Request) -> Result<SessionTuple> {
-        if let Some(ext) = req.extensions_mut().remove::<Option<SessionTuple>>() {
-            if let Some(tuple) = ext {
-                Ok(tuple)
-            } else {
-                Err(Error::new(401, "NoSession", "no session is available"))
-            }
-        } else {
-            panic!("no session tuple is present in the request extension");
-        }
-    }
-
-    pub fn try_from_user(req: &mut Request) -> Result<User> {
-        if let Some(ext) = req.extensions_mut().remove::<Option<SessionTuple>>() {
-            if let Some(tuple) = ext {
-                Ok(tuple.0)
-            } else {
-                Err(Error::new(401, "NoSession", "no session is available"))
-            }
-        } else {
-            panic!("no session tuple is present in the request extension");
-        }
-    }
-
-    pub fn try_from_session(req: &mut Request) -> Result<UserSession> {
-        if let Some(ext) = req.extensions_mut().remove::<Option<SessionTuple>>() {
-            if let Some(tuple) = ext {
-                Ok(tuple.1)
-            } else {
-                Err(Error::new(401, "NoSession", "no session is available"))
-            }
-        } else {
-            panic!("no session tuple is present in the request extension");
-        }
-    }
-
-    pub fn option_try_from(req: &mut Request) -> Option<SessionTuple> {
-        if let Some(ext) = req.extensions_mut().remove() {
-            ext
-        } else {
-            panic!("no session tuple is present in the request extension");
-        }
-    }
-
-    pub fn option_try_from_user(req: &mut Request) -> Option<User> {
-        if let Some(ext) = req.extensions_mut().remove::<Option<SessionTuple>>() {
-            if let Some(tuple) = ext {
-                Some(tuple.0)
-            } else {
-                None
-            }
-        } else {
-            panic!("no session tuple is present in the request extension");
-        }
-    }
-
-    pub fn option_try_from_user_session(req: &mut Request) -> Option<UserSession> {
-        if let Some(ext) = req.extensions_mut().remove::<Option<SessionTuple>>() {
-            if let Some(tuple) = ext {
-                Some(tuple.1)
-            } else {
-                None
-            }
-        } else {
-            panic!("no session tuple is present in the request extension");
-        }
-    }
-
-    pub fn into_tuple(self) -> (User, UserSession) {
-        (self.0, self.1)
-    }
-
-    pub fn into_user(self) -> User {
-        self.0
-    }
-
-    pub fn into_session(self) -> UserSession {
-        self.1
-    }
+// takes the session out of the request extensions. only routes that are
+// public will not have one checked
+pub fn take_session(ext: &mut Extensions) -> Option<(User, UserSession)> {
+    ext.remove::<Option<SessionTuple>>()
+        .flatten()
+        .map(|tuple| (tuple.0, tuple.1))
 }
-*/
 
 pub async fn get_session(conn: &impl GenericClient, headers: &HeaderMap) -> Result<Option<(User, UserSession)>> {
     if let Some(_auth) = headers.get("authorization") {
         // do something
-        return Err(Error::new(400, "NotImplemented", "bot sessions are not currently enabled"));
+        Err(Error::new(400, "NotImplemented", "bot sessions are not currently enabled"))
     } else {
         let cookies = get_cookie_map(headers);
         let session_id_key = "session_id".to_owned();
 
         if let Some(list) = cookies.get(&session_id_key) {
             if let Ok(session_id) = &list[0].parse::<uuid::Uuid>() {
-                if let Some(session) = UserSession::find_token(&*conn, session_id).await? {
+                if let Some(session) = UserSession::find_token(conn, session_id).await? {
                     let now = Utc::now();
 
                     if session.dropped || session.expires < now {
@@ -131,14 +56,30 @@ pub async fn get_session(conn: &impl GenericClient, headers: &HeaderMap) -> Resu
     }
 }
 
-pub async fn require_session(conn: &impl GenericClient, headers: &HeaderMap) -> Result<(User, UserSession)> {
-    if let Some(tuple) = get_session(conn, headers).await? {
+pub fn require_session(ext: &mut Extensions) -> Result<(User, UserSession)> {
+    if let Some(tuple) = take_session(ext) {
         Ok(tuple)
     } else {
         Err(Error::new(401, "NoSession", "no session is available"))
     }
 }
 
+pub fn require_admin(user: &User) -> Result<()> {
+    if user.is_admin() {
+        Ok(())
+    } else {
+        Err(Error::new(403, "PermissionDenied", "only administrators can perform this action"))
+    }
+}
+
+pub fn require_self_or_admin(user: &User, users_id: &i64) -> Result<()> {
+    if user.id == *users_id {
+        Ok(())
+    } else {
+        require_admin(user)
+    }
+}
+
 pub fn login_redirect_path(path: &str) -> Result<Response> {
     let redirect_path = format!("/auth/session?jump_to={}", urlencoding::encode(path));
     redirect_response(&redirect_path)
@@ -151,16 +92,16 @@ pub fn login_redirect(uri: &Uri) -> Result<Response> {
         uri.path()
     };
 
-    login_redirect_path(&redirect_path)
+    login_redirect_path(redirect_path)
 }
 
 pub fn verify_totp_code(user: &User, code: String) -> Result<()> {
     let secret = user.totp_secret.clone()
         .unwrap();
-    let step: u64 = user.totp_step.clone()
+    let step: u64 = user.totp_step
         .unwrap()
         .into();
-    let digits: u32 = user.totp_digits.clone()
+    let digits: u32 = user.totp_digits
         .unwrap()
         .into();
     let now = unix_epoch_sec_now().unwrap();
@@ -12,7 +12,7 @@ pub fn validate_basename(basename: &str) -> Result<String> {
     Ok(trim.to_owned())
 }
 
-pub fn parse_new_context<'a>(context: &'a str) -> (&'a str, Option<&'a str>) {
+pub fn parse_new_context(context: &str) -> (&str, Option<&str>) {
     if let Some((parent, basename)) = context.rsplit_once('/') {
         (basename, Some(parent))
     } else {
@@ -14,7 +14,7 @@ use crate::{
 
 pub fn check_if_html_headers(headers: &HeaderMap) -> Result<bool> {
     if let Some(value) = headers.get("accept") {
-        for mime in value.to_str()?.split(",").map(|v| mime::Mime::from_str(v)) {
+        for mime in value.to_str()?.split(",").map(mime::Mime::from_str) {
             if let Ok(mime) = mime {
                 if mime.type_() == "text" && mime.subtype() == "html" {
                     return Ok(true)
@@ -1,3 +1,7 @@
 pub mod auth;
 pub mod html;
-pub mod fs_items;
\ No newline at end of file
+pub mod fs_items;
+pub mod users;
+pub mod quota;
+pub mod audit;
+pub mod sync;
\ No newline at end of file
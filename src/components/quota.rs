@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use hyper::{Body, HeaderMap, header::CONTENT_LENGTH};
+use serde::Serialize;
+use tokio::fs::{copy, remove_file, rename};
+use tokio_postgres::GenericClient;
+
+use crate::{
+    db::record::{User, FsItemType},
+    http::{body::{file_from_body, FileBody}, error::{Result, Error}},
+    state::AppState
+};
+
+#[derive(Debug, Serialize)]
+pub struct Quota {
+    pub users_id: i64,
+    pub usage: i64,
+    pub limit: Option<i64>
+}
+
+impl Quota {
+
+    // the number of bytes left before the limit is reached. none means
+    // there is no limit
+    pub fn remaining(&self) -> Option<u64> {
+        self.limit.map(|limit| {
+            if self.usage >= limit {
+                0
+            } else {
+                (limit - self.usage) as u64
+            }
+        })
+    }
+}
+
+pub fn quota_exceeded() -> Error {
+    Error::new(413, "QuotaExceeded", "the upload would exceed the storage quota for the user")
+}
+
+pub async fn get_usage(conn: &impl GenericClient, users_id: &i64) -> Result<i64> {
+    let file_type: i16 = FsItemType::File.into();
+    let record = conn.query_one(
+        "\
+        select coalesce(sum(item_size), 0)::bigint \
+        from fs_items \
+        where users_id = $1 and \
+              item_type = $2 and \
+              item_exists = true",
+        &[users_id, &file_type]
+    ).await?;
+
+    Ok(record.get(0))
+}
+
+pub async fn get_quota(state: &AppState, conn: &impl GenericClient, user: &User) -> Result<Quota> {
+    let limit = user.quota.or(state.storage.quota.map(|v| v as i64));
+
+    Ok(Quota {
+        users_id: user.id,
+        usage: get_usage(conn, &user.id).await?,
+        limit
+    })
+}
+
+// checks the content length of a request, if present, against the amount of
+// space available. will return the limit that should be applied while reading
+// the body. replacing is the size of the item that will be overwritten.
+pub fn check_upload(quota: &Quota, replacing: i64, headers: &HeaderMap) -> Result<Option<u64>> {
+    let limit = quota.remaining().map(|remaining| remaining + (replacing.max(0) as u64));
+
+    if let Some(limit) = limit {
+        if let Some(value) = headers.get(CONTENT_LENGTH) {
+            let length: u64 = value.to_str()?
+                .parse()
+                .map_err(|_| Error::new(400, "InvalidContentLength", "the given content-length is not a valid integer"))?;
+
+            if length > limit {
+                return Err(quota_exceeded());
+            }
+        }
+    }
+
+    Ok(limit)
+}
+
+// writes the body to a file in the temporary directory and then moves it
+// into place so that a failed upload will not clobber an existing file
+pub async fn write_upload(state: &AppState, path: &Path, body: Body, limit: Option<u64>) -> Result<usize> {
+    let tmp_path = state.storage.get_tmp_file("upload");
+
+    match file_from_body(&tmp_path, false, body, limit).await {
+        Ok(FileBody::Written(file, size)) => {
+            drop(file);
+
+            // the temporary directory can be on a different device than
+            // storage in which case the file has to be copied over
+            if rename(&tmp_path, path).await.is_err() {
+                let result = copy(&tmp_path, path).await;
+
+                remove_tmp_upload(&tmp_path).await;
+
+                result?;
+            }
+
+            Ok(size)
+        },
+        // the limit given is whatever is left of the quota for the user
+        Ok(FileBody::OverLimit) => {
+            remove_tmp_upload(&tmp_path).await;
+
+            Err(quota_exceeded())
+        },
+        Err(err) => {
+            remove_tmp_upload(&tmp_path).await;
+
+            Err(err)
+        }
+    }
+}
+
+async fn remove_tmp_upload(tmp_path: &Path) {
+    if let Err(err) = remove_file(tmp_path).await {
+        log::error!("failed to remove temporary upload {}. {}", tmp_path.display(), err);
+    }
+}
\ No newline at end of file
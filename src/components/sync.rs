@@ -0,0 +1,539 @@
+use std::{path::{Path, PathBuf}, fs::Metadata, io::Read};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ring::digest;
+use serde_json::json;
+use tokio::fs::{ReadDir, read_dir, metadata};
+use tokio::task::spawn_blocking;
+use tokio_postgres::GenericClient;
+
+use crate::{
+    state::AppState,
+    event,
+    http::error::{Result, Error},
+    db::record::{FsItem, FsItemType, join_path}
+};
+
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+struct WorkItem {
+    iter: ReadDir,
+    id: i64
+}
+
+#[derive(Serialize)]
+pub struct CreatedItem {
+    pub path: String,
+    pub item_type: FsItemType
+}
+
+#[derive(Serialize)]
+pub struct UpdatedItem {
+    pub id: i64,
+    pub path: String,
+    #[serde(flatten)]
+    pub change: Option<FileChange>
+}
+
+#[derive(Serialize)]
+pub struct MissingItem {
+    pub id: i64,
+    pub path: String
+}
+
+// detailed list of what a sync changed or, for a dry run, would change
+#[derive(Default, Serialize)]
+pub struct SyncDiff {
+    pub created: Vec<CreatedItem>,
+    pub updated: Vec<UpdatedItem>,
+    pub missing: Vec<MissingItem>
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct SyncOptions {
+    // fill in the diff of the result
+    pub report: bool,
+    // hash file contents to decide if a file changed
+    pub deep: bool
+}
+
+#[derive(Default)]
+pub struct SyncResult {
+    pub created: u64,
+    pub updated: u64,
+    pub missing: u64,
+    pub diff: SyncDiff
+}
+
+impl SyncResult {
+    pub fn has_changes(&self) -> bool {
+        self.created > 0 || self.updated > 0 || self.missing > 0
+    }
+}
+
+pub fn path_to_str(path: &Path) -> Result<&str> {
+    path.to_str().ok_or(Error::new(400, "NonUtf8Path", "encountered a file system path that cannot be converted to utf-8"))
+}
+
+pub fn get_directory_and_basename(app: &AppState, fs_path: &Path) -> Result<(String, String)> {
+    let storage_str = path_to_str(&app.storage.directory)?;
+    let working = path_to_str(fs_path)?;
+
+    Ok(lib::string::get_directory_and_basename(match working.strip_prefix(storage_str) {
+        Some(stripped) => {
+            if let Some(stripped_leading) = stripped.strip_prefix("/") {
+                stripped_leading
+            } else {
+                stripped
+            }
+        },
+        None => working
+    }, true))
+}
+
+// the before and after values of a file that was changed on disk
+#[derive(Debug, Serialize)]
+pub struct FileChange {
+    pub old_size: i64,
+    pub new_size: i64,
+    pub old_modified: Option<DateTime<Utc>>,
+    pub new_modified: Option<DateTime<Utc>>
+}
+
+// reads the file on the blocking pool so that hashing large files does not
+// hold up the runtime
+pub async fn hash_file(file_path: &Path) -> Result<Vec<u8>> {
+    let file_path = file_path.to_owned();
+
+    Ok(spawn_blocking(move || -> std::io::Result<Vec<u8>> {
+        let mut file = std::fs::File::open(&file_path)?;
+        let mut context = digest::Context::new(&digest::SHA256);
+        let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+
+        loop {
+            let read = file.read(&mut buffer)?;
+
+            if read == 0 {
+                break;
+            }
+
+            context.update(&buffer[..read]);
+        }
+
+        Ok(context.finish().as_ref().to_vec())
+    }).await??)
+}
+
+// compares the file on disk with the record. when deep is true the contents
+// are hashed and, if a checksum was stored previously, only a different hash
+// or size counts as a change. timestamps are still updated but not reported
+pub async fn sync_known_file(conn: &impl GenericClient, file_path: &Path, md: &Metadata, item: &FsItem, deep: bool) -> Result<Option<FileChange>> {
+    let mut updated = false;
+    let mut created_value = item.created;
+    let mut modified_value = item.modified;
+    let mut item_size_value = item.item_size;
+
+    if let Ok(created) = md.created() {
+        let stamp = created.into();
+
+        if created_value != stamp {
+            updated = true;
+            created_value = stamp;
+        }
+    }
+
+    if let Ok(modified) = md.modified() {
+        let stamp = modified.into();
+
+        if let Some(modify) = modified_value {
+            if modify != stamp {
+                updated = true;
+                modified_value = Some(stamp);
+            } else {
+                modified_value = Some(modify);
+            }
+        } else {
+            updated = true;
+            modified_value = Some(stamp);
+        }
+    }
+
+    let md_size = md.len() as i64;
+    let size_changed = md_size != item_size_value;
+
+    if size_changed {
+        updated = true;
+        item_size_value = md_size;
+    }
+
+    let change = FileChange {
+        old_size: item.item_size,
+        new_size: item_size_value,
+        old_modified: item.modified,
+        new_modified: modified_value
+    };
+
+    if deep {
+        let stored: Option<Vec<u8>> = conn.query_one(
+            "select checksum from fs_items where id = $1",
+            &[&item.id]
+        ).await?.get(0);
+        let checksum = hash_file(file_path).await?;
+        let checksum_changed = stored.as_ref() != Some(&checksum);
+
+        let content_changed = if stored.is_some() {
+            checksum_changed || size_changed
+        } else {
+            updated
+        };
+
+        if updated || checksum_changed {
+            conn.execute(
+                "\
+                update fs_items \
+                set created = $2, \
+                    modified = $3, \
+                    item_size = $4, \
+                    checksum = $5, \
+                    item_exists = true \
+                where id = $1",
+                &[&item.id, &created_value, &modified_value, &item_size_value, &checksum]
+            ).await?;
+        }
+
+        if content_changed {
+            Ok(Some(change))
+        } else {
+            Ok(None)
+        }
+    } else if updated {
+        // the contents may have changed so the checksum can no longer be
+        // trusted
+        conn.execute(
+            "update fs_items set created = $2, modified = $3, item_size = $4, checksum = null, item_exists = true where id = $1", 
+            &[&item.id, &created_value, &modified_value, &item_size_value]
+        ).await?;
+
+        Ok(Some(change))
+    } else {
+        Ok(None)
+    }
+}
+
+pub async fn sync_file(app: &AppState, conn: &impl GenericClient, users_id: &i64, parent: &i64, file_path: &PathBuf, deep: bool) -> Result<(i64, bool, Option<FileChange>)> {
+    let md = metadata(&file_path).await?;
+    let (directory, basename) = get_directory_and_basename(app, file_path)?;
+
+    if let Some(item) = FsItem::find_user_id_directory_basename(conn, users_id, &directory, &basename).await? {
+        Ok((item.id, false, sync_known_file(conn, file_path, &md, &item, deep).await?))
+    } else {
+        let id = app.snowflakes.fs_items.next_id().await?;
+        let created: DateTime<Utc> = if let Ok(c) = md.created() {
+            c.into()
+        } else {
+            Utc::now()
+        };
+        let modified: Option<DateTime<Utc>> = if let Ok(m) = md.modified() {
+            Some(m.into())
+        } else {
+            None
+        };
+
+        FsItem {
+            id,
+            item_type: FsItemType::File,
+            parent: Some(*parent),
+            users_id: *users_id,
+            directory,
+            basename,
+            item_size: md.len() as i64,
+            created,
+            modified,
+            item_exists: true,
+            user_data: json!({}),
+            is_root: false
+        }.create(conn).await?;
+
+        if deep {
+            let checksum = hash_file(file_path).await?;
+
+            conn.execute(
+                "update fs_items set checksum = $2 where id = $1",
+                &[&id, &checksum]
+            ).await?;
+        }
+
+        Ok((id, true, None))
+    }
+}
+
+pub async fn sync_dir(app: &AppState, conn: &impl GenericClient, users_id: &i64, parent: &i64, dir_path: &PathBuf) -> Result<(i64, bool, bool)> {
+    let (directory, basename) = get_directory_and_basename(app, dir_path)?;
+
+    if let Some(fs_item) = FsItem::find_user_id_directory_basename(conn, users_id, &directory, &basename).await? {
+        // only counts as updated if the directory was previously missing
+        if !fs_item.item_exists {
+            conn.execute(
+                "update fs_items set item_exists = true where id = $1",
+                &[&fs_item.id]
+            ).await?;
+        }
+
+        Ok((fs_item.id, false, !fs_item.item_exists))
+    } else {
+        let md = metadata(dir_path).await?;
+        let id = app.snowflakes.fs_items.await_next_id().await?;
+        let created: DateTime<Utc> = if let Ok(c) = md.created() {
+            c.into()
+        } else {
+            Utc::now()
+        };
+
+        FsItem {
+            id,
+            item_type: FsItemType::Dir,
+            parent: Some(*parent),
+            users_id: *users_id,
+            directory,
+            basename,
+            item_size: 0,
+            created,
+            modified: None,
+            item_exists: true,
+            user_data: json!({}),
+            is_root: false
+        }.create(conn).await?;
+
+        Ok((id, true, false))
+    }
+}
+
+// removes the item and everything under it from the database, notifying any
+// listeners of the delete
+pub async fn remove_item(state: &AppState, item: FsItem) -> Result<()> {
+    let mut conn = state.db.pool.get().await?;
+    let transaction = conn.transaction().await?;
+
+    // the item may have already been removed by a request that is finishing
+    // up so the rows are locked before anything else is done
+    let ids: Vec<i64> = transaction.query(
+        "\
+        with recursive dir_tree as ( \
+            select fs_root.id \
+            from fs_items fs_root \
+            where id = $1 \
+            union \
+            select fs_contents.id \
+            from fs_items fs_contents \
+            inner join dir_tree on dir_tree.id = fs_contents.parent \
+        ) \
+        select fs_items.id \
+        from fs_items \
+        join dir_tree on dir_tree.id = fs_items.id \
+        for update of fs_items",
+        &[&item.id]
+    ).await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let (delivery_count, bus_event) = event::enqueue_fs_item_deleted(&transaction, &item, &ids).await?;
+
+    transaction.execute(
+        "delete from event_listeners where ref_table = 'fs_items' and ref_id = any($1)",
+        &[&ids]
+    ).await?;
+
+    transaction.execute(
+        "delete from fs_items where id = any($1)",
+        &[&ids]
+    ).await?;
+
+    transaction.commit().await?;
+
+    state.events.publish(bus_event);
+
+    if delivery_count > 0 {
+        state.events.notify();
+    }
+
+    Ok(())
+}
+
+// walks the disk under the given item and brings the records in line with
+// what is found
+pub async fn sync_item(app: &AppState, conn: &impl GenericClient, fs_item: &FsItem, options: SyncOptions) -> Result<SyncResult> {
+    let SyncOptions { report, deep } = options;
+    let mut result = SyncResult::default();
+
+    if fs_item.item_type == FsItemType::Dir {
+        let mut found_ids: Vec<i64> = Vec::with_capacity(1);
+        let mut fs_path = app.storage.directory.clone();
+        fs_path.push(&fs_item.directory);
+        fs_path.push(&fs_item.basename);
+
+        if fs_path.exists() {
+            let mut working_queue = Vec::with_capacity(1);
+            working_queue.push(WorkItem {
+                iter: read_dir(&fs_path).await?,
+                id: fs_item.id
+            });
+            found_ids.push(fs_item.id);
+
+            while let Some(mut working) = working_queue.pop() {
+                while let Some(entry) = working.iter.next_entry().await? {
+                    let entry_path = entry.path();
+
+                    if entry_path.is_dir() {
+                        let (id, created, updated) = sync_dir(
+                            app, 
+                            conn, 
+                            &fs_item.users_id, 
+                            &working.id, 
+                            &entry_path
+                        ).await?;
+
+                        if created {
+                            result.created += 1;
+                        } else if updated {
+                            result.updated += 1;
+                        }
+
+                        if report && (created || updated) {
+                            let (directory, basename) = get_directory_and_basename(app, &entry_path)?;
+                            let path = join_path(&directory, &basename);
+
+                            if created {
+                                result.diff.created.push(CreatedItem { path, item_type: FsItemType::Dir });
+                            } else {
+                                result.diff.updated.push(UpdatedItem { id, path, change: None });
+                            }
+                        }
+
+                        found_ids.push(id);
+                        working_queue.push(working);
+                        working_queue.push(WorkItem {
+                            iter: read_dir(&entry_path).await?,
+                            id
+                        });
+
+                        break;
+                    } else if entry_path.is_file() {
+                        let (id, created, change) = sync_file(
+                            app, 
+                            conn, 
+                            &fs_item.users_id, 
+                            &working.id, 
+                            &entry_path,
+                            deep
+                        ).await?;
+
+                        if created {
+                            result.created += 1;
+                        } else if change.is_some() {
+                            result.updated += 1;
+                        }
+
+                        if report && (created || change.is_some()) {
+                            let (directory, basename) = get_directory_and_basename(app, &entry_path)?;
+                            let path = join_path(&directory, &basename);
+
+                            if created {
+                                result.diff.created.push(CreatedItem { path, item_type: FsItemType::File });
+                            } else {
+                                result.diff.updated.push(UpdatedItem { id, path, change });
+                            }
+                        }
+
+                        found_ids.push(id);
+                    } else {
+                        // unknown file type
+                    }
+                }
+            }
+
+            conn.execute(
+                "update fs_items set item_exists = true where id = $1",
+                &[&fs_item.id]
+            ).await?;
+        }
+
+        let missing = conn.query(
+            "\
+            with recursive dir_tree as ( \
+                select fs_root.id, \
+                       fs_root.parent, \
+                       1 as level \
+                from fs_items fs_root \
+                where id = $1 \
+                union \
+                select fs_contents.id, \
+                       fs_contents.parent, \
+                       dir_tree.level + 1 as level \
+                from fs_items fs_contents \
+                inner join dir_tree on dir_tree.id = fs_contents.parent \
+            ) \
+            update fs_items \
+            set item_exists = false, \
+                missing_since = now() \
+            from dir_tree \
+            where dir_tree.id = fs_items.id and \
+                  fs_items.item_exists = true and \
+                  dir_tree.id <> all($2) \
+            returning fs_items.id, \
+                      fs_items.directory, \
+                      fs_items.basename",
+            &[&fs_item.id, &found_ids]
+        ).await?;
+
+        result.missing = missing.len() as u64;
+
+        if report {
+            for row in missing {
+                result.diff.missing.push(MissingItem {
+                    id: row.get(0),
+                    path: join_path(row.get(1), row.get(2))
+                });
+            }
+        }
+    } else if fs_item.item_type == FsItemType::File {
+        let mut fs_path = app.storage.directory.clone();
+        fs_path.push(&fs_item.directory);
+        fs_path.push(&fs_item.basename);
+
+        if fs_path.exists() {
+            let md = metadata(&fs_path).await?;
+
+            if let Some(change) = sync_known_file(conn, &fs_path, &md, fs_item, deep).await? {
+                result.updated += 1;
+
+                if report {
+                    result.diff.updated.push(UpdatedItem {
+                        id: fs_item.id,
+                        path: join_path(&fs_item.directory, &fs_item.basename),
+                        change: Some(change)
+                    });
+                }
+            }
+        } else {
+            result.missing = conn.execute(
+                "update fs_items set item_exists = false, missing_since = now() where id = $1 and item_exists = true",
+                &[&fs_item.id]
+            ).await?;
+
+            if report && result.missing > 0 {
+                result.diff.missing.push(MissingItem {
+                    id: fs_item.id,
+                    path: join_path(&fs_item.directory, &fs_item.basename)
+                });
+            }
+        }
+    }
+
+    Ok(result)
+}
\ No newline at end of file
@@ -0,0 +1,291 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde_json::json;
+use tokio::fs::{create_dir, remove_dir_all, rename};
+use tokio_postgres::GenericClient;
+
+use crate::{
+    db::record::{User, UserRole, FsItem, FsItemType},
+    http::error::{Result, Error},
+    security::{argon::hash_with_default, rand::rand_hex},
+    state::AppState
+};
+
+pub struct NewUser {
+    pub username: String,
+    pub password: String,
+    pub email: Option<String>,
+    pub role: UserRole
+}
+
+// checks to make sure that the username and optional email are not already
+// in use by another user
+pub async fn check_available(conn: &impl GenericClient, username: &String, email: &Option<String>) -> Result<()> {
+    let existing = User::find_username_or_optional_email(conn, username, email).await?;
+
+    if let Some(record) = existing.into_iter().next() {
+        if record.username == *username {
+            return Err(Error::new(400, "UsernameInUse", "the requested username is already in use"))
+        } else {
+            return Err(Error::new(400, "EmailInUse", "the requested email is already in use"))
+        }
+    }
+
+    Ok(())
+}
+
+// the root directory of a newly created user. it is removed when dropped
+// unless the transaction that created the user was committed
+pub struct NewUserRoot {
+    path: Option<PathBuf>
+}
+
+impl NewUserRoot {
+    pub fn committed(mut self) {
+        self.path = None;
+    }
+}
+
+impl Drop for NewUserRoot {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            if let Err(err) = std::fs::remove_dir(&path) {
+                log::error!("failed to remove root directory {}. {}", path.display(), err);
+            }
+        }
+    }
+}
+
+// changes to storage from removing a user. these are applied after the
+// transaction is committed so that a rollback does not leave the database
+// pointing at files that were already moved or removed
+pub enum RootAction {
+    Move(PathBuf, PathBuf),
+    Remove(PathBuf)
+}
+
+impl RootAction {
+    pub async fn apply(self, state: &AppState) {
+        let (result, path) = match self {
+            RootAction::Move(from, to) => {
+                let _from_write = state.storage.writes.track_tree(from.clone());
+                let _to_write = state.storage.writes.track_tree(to.clone());
+
+                (rename(&from, &to).await, from)
+            },
+            RootAction::Remove(path) => {
+                let _write = state.storage.writes.track_tree(path.clone());
+
+                (remove_dir_all(&path).await, path)
+            }
+        };
+
+        if let Err(err) = result {
+            if err.kind() != ErrorKind::NotFound {
+                log::error!("failed to update user root directory {}. {}", path.display(), err);
+            }
+        }
+    }
+}
+
+// creates the user record along with the root directory record and the root
+// directory in storage
+pub async fn create_user(state: &AppState, conn: &impl GenericClient, new_user: NewUser) -> Result<(User, NewUserRoot)> {
+    let user = {
+        let id = state.snowflakes.users.next_id().await?;
+        let hash = hash_with_default(&new_user.password)?;
+        let role: i16 = new_user.role.clone().into();
+
+        conn.execute(
+            "\
+            insert into users (id, username, hash, email, role) values \
+            ($1, $2, $3, $4, $5)",
+            &[&id, &new_user.username, &hash, &new_user.email, &role]
+        ).await?;
+
+        User {
+            id,
+            username: new_user.username,
+            hash,
+            email: new_user.email,
+            email_verified: false,
+            totp_enabled: false,
+            totp_algorithm: None,
+            totp_secret: None,
+            totp_step: None,
+            totp_digits: None,
+            role: new_user.role,
+            quota: None
+        }
+    };
+
+    {
+        let root = FsItem {
+            id: state.snowflakes.fs_items.next_id().await?,
+            item_type: FsItemType::Dir,
+            parent: None,
+            users_id: user.id,
+            directory: "".to_owned(),
+            basename: user.id.to_string(),
+            item_size: 0,
+            created: Utc::now(),
+            modified: None,
+            item_exists: true,
+            user_data: json!({}),
+            is_root: true
+        };
+
+        root.create(conn).await?;
+    }
+
+    let mut root_path = state.storage.directory.clone();
+    root_path.push(user.id.to_string());
+
+    create_dir(&root_path).await?;
+
+    Ok((user, NewUserRoot { path: Some(root_path) }))
+}
+
+// creates a new verification key for the email of the given user. any
+// previous keys for the user will be removed
+pub async fn create_email_verification(conn: &impl GenericClient, user: &User) -> Result<String> {
+    let key_id = rand_hex(32).ok_or(Error::from("failed to get random bytes from system"))?;
+    let issued = Utc::now();
+
+    conn.execute(
+        "delete from email_verificiations where users_id = $1",
+        &[&user.id]
+    ).await?;
+
+    conn.execute(
+        "insert into email_verificiations (users_id, key_id, issued) values ($1, $2, $3)",
+        &[&user.id, &key_id, &issued]
+    ).await?;
+
+    Ok(key_id)
+}
+
+// moves the root directory of the given user into the root directory of the
+// target user. the old root will become a regular directory named after the
+// id of the user being removed. usernames are not checked for characters
+// that are unsafe in a path so they are not used
+async fn transfer_fs_items(state: &AppState, conn: &impl GenericClient, user: &User, target: &User) -> Result<Option<RootAction>> {
+    let target_root = FsItem::find_user_root(conn, &target.id).await?
+        .ok_or(Error::new(500, "MissingRootDirectory", "the transfer target does not have a root directory"))?;
+
+    if let Some(user_root) = FsItem::find_user_root(conn, &user.id).await? {
+        let mut basename = user.id.to_string();
+        let mut count: u32 = 1;
+
+        while FsItem::find_basename_with_parent(conn, &target_root.id, &basename).await?.is_some() {
+            basename = format!("{}_{}", user.id, count);
+            count += 1;
+        }
+
+        let old_prefix = user_root.basename.clone();
+        let new_prefix = format!("{}/{}", target_root.basename, basename);
+
+        conn.execute(
+            "\
+            update fs_items \
+            set parent = $2, \
+                users_id = $3, \
+                directory = $4, \
+                basename = $5, \
+                is_root = false \
+            where id = $1",
+            &[&user_root.id, &target_root.id, &target.id, &target_root.basename, &basename]
+        ).await?;
+
+        conn.execute(
+            "\
+            update fs_items \
+            set users_id = $2, \
+                directory = regexp_replace(directory, '^(/?)' || $3 || '(/|$)', '\\1' || $4 || '\\2') \
+            where users_id = $1",
+            &[&user.id, &target.id, &old_prefix, &new_prefix]
+        ).await?;
+
+        let mut from_path = state.storage.directory.clone();
+        from_path.push(&old_prefix);
+
+        let mut to_path = state.storage.directory.clone();
+        to_path.push(&new_prefix);
+
+        Ok(Some(RootAction::Move(from_path, to_path)))
+    } else {
+        conn.execute(
+            "update fs_items set users_id = $2 where users_id = $1",
+            &[&user.id, &target.id]
+        ).await?;
+
+        Ok(None)
+    }
+}
+
+// removes the given user along with their sessions, listeners, and file
+// system items. if a target is given then the file system items are moved
+// to the target instead of being removed. the returned action must be
+// applied once the transaction has been committed
+pub async fn delete_user(state: &AppState, conn: &impl GenericClient, user: &User, transfer: Option<&User>) -> Result<Option<RootAction>> {
+    conn.execute(
+        "delete from user_sessions where users_id = $1",
+        &[&user.id]
+    ).await?;
+
+    conn.execute(
+        "delete from email_verificiations where users_id = $1",
+        &[&user.id]
+    ).await?;
+
+    conn.execute(
+        "delete from event_deliveries where users_id = $1",
+        &[&user.id]
+    ).await?;
+
+    conn.execute(
+        "delete from event_listeners where users_id = $1",
+        &[&user.id]
+    ).await?;
+
+    conn.execute(
+        "delete from user_invites where issued_by = $1",
+        &[&user.id]
+    ).await?;
+
+    conn.execute(
+        "update user_invites set used_by = null where used_by = $1",
+        &[&user.id]
+    ).await?;
+
+    let action = if let Some(target) = transfer {
+        transfer_fs_items(state, conn, user, target).await?
+    } else {
+        conn.execute(
+            "\
+            delete from event_listeners \
+            where ref_table = 'fs_items' and \
+                  ref_id in (select id from fs_items where users_id = $1)",
+            &[&user.id]
+        ).await?;
+
+        conn.execute(
+            "delete from fs_items where users_id = $1",
+            &[&user.id]
+        ).await?;
+
+        let mut root_path = state.storage.directory.clone();
+        root_path.push(user.id.to_string());
+
+        Some(RootAction::Remove(root_path))
+    };
+
+    conn.execute(
+        "delete from users where id = $1",
+        &[&user.id]
+    ).await?;
+
+    Ok(action)
+}
\ No newline at end of file
@@ -3,6 +3,7 @@ use std::convert::{From};
 use std::ffi::{OsString};
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     InvalidConfig(String),
 
@@ -49,7 +50,7 @@ impl fmt::Display for Error {
                 }
             },
             Error::YamlError(err) => {
-                write!(f, "yaml error {}", err.to_string())
+                write!(f, "yaml error {}", err)
             },
             Error::IOError(err) => write!(f, "std::io::Error {:?}", err),
             Error::FMTError(err) => write!(f, "std::fmt::Error {:?}", err),
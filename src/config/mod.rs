@@ -78,6 +78,7 @@ impl TryFrom<Option<shape::EmailShape>> for EmailConfig {
 
 #[derive(Debug)]
 pub struct ServerInfoConfig {
+    #[allow(dead_code)]
     pub secure: bool,
     pub origin: String,
     pub name: String
@@ -114,7 +115,7 @@ impl TryFrom<Option<shape::TemplateShape>> for TemplateConfig {
     type Error = error::Error;
 
     fn try_from(value: Option<shape::TemplateShape>) -> error::Result<TemplateConfig> {
-        let default_dir = std::env::current_dir().unwrap_or(PathBuf::new());
+        let default_dir = std::env::current_dir().unwrap_or_default();
 
         if let Some(v) = value {
             Ok(TemplateConfig {
@@ -180,15 +181,267 @@ impl TryFrom<Option<shape::WatcherShape>> for WatcherConfig {
     }
 }
 
+// 100 years
+const MAX_PURGE_AFTER: u64 = 60 * 60 * 24 * 365 * 100;
+
+#[derive(Debug)]
+pub struct SchedulerConfig {
+    pub enable: bool,
+    // seconds between each reconciliation of user storage
+    pub sync_interval: u64,
+    // number of user roots that are synced at the same time
+    pub concurrency: usize,
+    // seconds an item can be missing before its record is removed. nothing is
+    // removed if not given
+    pub purge_after: Option<u64>
+}
+
+impl TryFrom<Option<shape::SchedulerShape>> for SchedulerConfig {
+    type Error = error::Error;
+
+    fn try_from(value: Option<shape::SchedulerShape>) -> error::Result<SchedulerConfig> {
+        if let Some(v) = value {
+            let sync_interval = v.sync_interval.unwrap_or(60 * 60);
+            let concurrency = v.concurrency.unwrap_or(2);
+
+            if sync_interval == 0 {
+                return Err(error::Error::InvalidConfig(
+                    "conf.scheduler.sync_interval must be greater than 0".to_string()
+                ));
+            }
+
+            if concurrency == 0 {
+                return Err(error::Error::InvalidConfig(
+                    "conf.scheduler.concurrency must be greater than 0".to_string()
+                ));
+            }
+
+            if let Some(purge_after) = v.purge_after {
+                // anything larger will overflow when creating the cutoff
+                // timestamp
+                if purge_after == 0 || purge_after > MAX_PURGE_AFTER {
+                    return Err(error::Error::InvalidConfig(
+                        format!("conf.scheduler.purge_after must be greater than 0 and at most {}", MAX_PURGE_AFTER)
+                    ));
+                }
+            }
+
+            Ok(SchedulerConfig {
+                enable: v.enable.unwrap_or(false),
+                sync_interval,
+                concurrency,
+                purge_after: v.purge_after
+            })
+        } else {
+            Ok(SchedulerConfig {
+                enable: false,
+                sync_interval: 60 * 60,
+                concurrency: 2,
+                purge_after: None
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompressionConfig {
+    pub enable: bool,
+    // responses with a known size smaller than this are sent as is
+    pub min_size: u64
+}
+
+impl TryFrom<Option<shape::CompressionShape>> for CompressionConfig {
+    type Error = error::Error;
+
+    fn try_from(value: Option<shape::CompressionShape>) -> error::Result<CompressionConfig> {
+        if let Some(v) = value {
+            Ok(CompressionConfig {
+                enable: v.enable.unwrap_or(true),
+                min_size: v.min_size.unwrap_or(1024)
+            })
+        } else {
+            Ok(CompressionConfig {
+                enable: true,
+                min_size: 1024
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub enable: bool,
+    // "*" allows any origin
+    pub origins: Vec<String>,
+    pub methods: Vec<String>,
+    pub headers: Vec<String>,
+    pub credentials: bool,
+    // seconds a preflight response can be cached by the browser
+    pub max_age: Option<u64>
+}
+
+impl TryFrom<Option<shape::CorsShape>> for CorsConfig {
+    type Error = error::Error;
+
+    fn try_from(value: Option<shape::CorsShape>) -> error::Result<CorsConfig> {
+        let default_methods = || -> Vec<String> {
+            ["GET", "HEAD", "POST", "PUT", "DELETE"].iter()
+                .map(|m| m.to_string())
+                .collect()
+        };
+
+        if let Some(v) = value {
+            let origins = v.origins.unwrap_or_default();
+            let methods: Vec<String> = v.methods.map(|list| {
+                list.into_iter()
+                    .map(|m| m.to_ascii_uppercase())
+                    .collect()
+            }).unwrap_or_else(default_methods);
+            let headers = v.headers.unwrap_or_else(|| vec!["content-type".to_owned()]);
+            let credentials = v.credentials.unwrap_or(false);
+
+            // browsers will not send credentials to a wildcard origin and
+            // echoing any origin back would let every site make
+            // authenticated requests
+            if credentials && origins.iter().any(|origin| origin == "*") {
+                return Err(error::Error::InvalidConfig(
+                    "conf.cors.origins cannot contain \"*\" when conf.cors.credentials is true".to_string()
+                ));
+            }
+
+            for origin in &origins {
+                if origin != "*" && !origin.starts_with("http://") && !origin.starts_with("https://") {
+                    return Err(error::Error::InvalidConfig(
+                        format!("conf.cors.origins contains an invalid origin \"{}\"", origin)
+                    ));
+                }
+
+                if origin.ends_with('/') {
+                    return Err(error::Error::InvalidConfig(
+                        format!("conf.cors.origins \"{}\" cannot end with a \"/\"", origin)
+                    ));
+                }
+            }
+
+            for method in &methods {
+                if hyper::Method::from_bytes(method.as_bytes()).is_err() {
+                    return Err(error::Error::InvalidConfig(
+                        format!("conf.cors.methods contains an invalid method \"{}\"", method)
+                    ));
+                }
+            }
+
+            for header in &headers {
+                if hyper::header::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                    return Err(error::Error::InvalidConfig(
+                        format!("conf.cors.headers contains an invalid header \"{}\"", header)
+                    ));
+                }
+            }
+
+            Ok(CorsConfig {
+                enable: v.enable.unwrap_or(!origins.is_empty()),
+                origins,
+                methods,
+                headers,
+                credentials,
+                max_age: v.max_age
+            })
+        } else {
+            Ok(CorsConfig {
+                enable: false,
+                origins: Vec::new(),
+                methods: default_methods(),
+                headers: vec!["content-type".to_owned()],
+                credentials: false,
+                max_age: None
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimitConfig {
+    // bytes. none will read the body until it ends
+    pub max_size: Option<u64>,
+    // seconds to wait between chunks of the body
+    pub idle_timeout: Option<u64>,
+    // seconds to receive the entire body
+    pub timeout: Option<u64>
+}
+
+impl BodyLimitConfig {
+    fn from_shape(name: &str, value: Option<shape::BodyLimitShape>, default: BodyLimitConfig) -> error::Result<BodyLimitConfig> {
+        let v = match value {
+            Some(v) => v,
+            None => return Ok(default)
+        };
+        let checks = [
+            ("max_size", v.max_size),
+            ("idle_timeout", v.idle_timeout),
+            ("timeout", v.timeout)
+        ];
+
+        for (field, given) in checks.iter() {
+            if *given == Some(0) {
+                return Err(error::Error::InvalidConfig(
+                    format!("conf.body.{}.{} must be greater than 0", name, field)
+                ));
+            }
+        }
+
+        Ok(BodyLimitConfig {
+            max_size: v.max_size.or(default.max_size),
+            idle_timeout: v.idle_timeout.or(default.idle_timeout),
+            timeout: v.timeout.or(default.timeout)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BodyConfig {
+    pub json: BodyLimitConfig,
+    // file uploads are only limited in size by the quota of the user unless
+    // a max is given
+    pub upload: BodyLimitConfig
+}
+
+impl TryFrom<Option<shape::BodyShape>> for BodyConfig {
+    type Error = error::Error;
+
+    fn try_from(value: Option<shape::BodyShape>) -> error::Result<BodyConfig> {
+        let json = BodyLimitConfig {
+            max_size: Some(1024 * 1024),
+            idle_timeout: Some(30),
+            timeout: Some(60)
+        };
+        let upload = BodyLimitConfig {
+            max_size: None,
+            idle_timeout: Some(60),
+            timeout: None
+        };
+
+        if let Some(v) = value {
+            Ok(BodyConfig {
+                json: BodyLimitConfig::from_shape("json", v.json, json)?,
+                upload: BodyLimitConfig::from_shape("upload", v.upload, upload)?
+            })
+        } else {
+            Ok(BodyConfig { json, upload })
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct BindInterfaceConfig {
     pub host: String,
-    pub port: u16
+    pub port: u16,
+    pub ssl: bool
 }
 
 impl BindInterfaceConfig {
 
-    pub fn to_sockaddr(self) -> error::Result<SocketAddr> {
+    pub fn into_sockaddr(self) -> error::Result<SocketAddr> {
         let host = self.host;
         let ip: IpAddr = host.parse().map_err(
             |_err| error::Error::InvalidIpAddr(host)
@@ -228,6 +481,7 @@ pub struct StorageConfig {
     pub directory: PathBuf,
     pub temporary: PathBuf,
     pub web_static: Option<PathBuf>,
+    pub quota: Option<u64>,
     pub static_: StorageStaticConfig
 }
 
@@ -238,13 +492,13 @@ impl TryFrom<Option<shape::StorageShape>> for StorageConfig {
         if let Some(v) = value {
             if v.directory.is_none() {
                 return Err(error::Error::InvalidConfig(
-                    format!("missing conf.storage.directory")
+                    "missing conf.storage.directory".to_string()
                 ))
             }
 
             if v.temporary.is_none() {
                 return Err(error::Error::InvalidConfig(
-                    format!("missing conf.storage.temporary")
+                    "missing conf.storage.temporary".to_string()
                 ))
             }
 
@@ -252,11 +506,12 @@ impl TryFrom<Option<shape::StorageShape>> for StorageConfig {
                 directory: v.directory.unwrap(),
                 temporary: v.temporary.unwrap(),
                 web_static: v.web_static,
+                quota: v.quota,
                 static_: v.static_.try_into()?
             })
         } else {
             Err(error::Error::InvalidConfig(
-                format!("missing conf.storage.directory and conf.storage.temporary")
+                "missing conf.storage.directory and conf.storage.temporary".to_string()
             ))
         }
     }
@@ -264,6 +519,7 @@ impl TryFrom<Option<shape::StorageShape>> for StorageConfig {
 
 #[derive(Debug)]
 pub struct SecurityConfig {
+    #[allow(dead_code)]
     pub secret: String
 }
 
@@ -292,6 +548,9 @@ pub struct ServerConfig {
     pub threads: usize,
     pub max_connections: usize,
     pub max_connection_rate: usize,
+    // seconds to wait for requests and background tasks to finish when
+    // shutting down
+    pub shutdown_timeout: u64,
 
     pub db: DBConfig,
     pub email: EmailConfig,
@@ -299,6 +558,11 @@ pub struct ServerConfig {
     pub ssl: SslConfig,
     pub template: TemplateConfig,
     pub watcher: WatcherConfig,
+    pub scheduler: SchedulerConfig,
+    pub compression: CompressionConfig,
+    pub cors: CorsConfig,
+    pub body: BodyConfig,
+    #[allow(dead_code)]
     pub security: SecurityConfig,
 }
 
@@ -307,6 +571,7 @@ impl TryFrom<shape::ServerShape> for ServerConfig {
 
     fn try_from(server_shape: shape::ServerShape) -> error::Result<ServerConfig> {
         let mut bind: Vec<BindInterfaceConfig>;
+        let ssl: SslConfig = server_shape.ssl.try_into()?;
 
         if let Some(interfaces) = server_shape.bind {
             bind = Vec::with_capacity(interfaces.len());
@@ -315,13 +580,20 @@ impl TryFrom<shape::ServerShape> for ServerConfig {
             for inter in interfaces {
                 bind.push(BindInterfaceConfig {
                     host: inter.host,
-                    port: inter.port.unwrap_or(port)
+                    port: inter.port.unwrap_or(port),
+                    ssl: inter.ssl.unwrap_or(ssl.enable)
                 })
             }
         } else {
             bind = Vec::new();
         }
 
+        if bind.iter().any(|inter| inter.ssl) && (ssl.key.is_none() || ssl.cert.is_none()) {
+            return Err(error::Error::InvalidConfig(
+                "conf.ssl.key and conf.ssl.cert are required when serving with ssl".to_string()
+            ));
+        }
+
         Ok(ServerConfig {
             storage: server_shape.storage.try_into()?,
             bind,
@@ -329,12 +601,17 @@ impl TryFrom<shape::ServerShape> for ServerConfig {
             backlog: server_shape.backlog.unwrap_or(2048),
             max_connections: server_shape.max_connections.unwrap_or(25000),
             max_connection_rate: server_shape.max_connection_rate.unwrap_or(256),
+            shutdown_timeout: server_shape.shutdown_timeout.unwrap_or(30),
             db: server_shape.db.try_into()?,
             email: server_shape.email.try_into()?,
             info: server_shape.info.try_into()?,
-            ssl: server_shape.ssl.try_into()?,
+            ssl,
             template: server_shape.template.try_into()?,
             watcher: server_shape.watcher.try_into()?,
+            scheduler: server_shape.scheduler.try_into()?,
+            compression: server_shape.compression.try_into()?,
+            cors: server_shape.cors.try_into()?,
+            body: server_shape.body.try_into()?,
             security: server_shape.security.try_into()?
         })
     }
@@ -348,7 +625,7 @@ pub fn load_server_config(files: Vec<PathBuf>) -> error::Result<ServerConfig> {
         let parent = file.parent().unwrap();
 
         base_shape.map_shape(shape::validate_server_shape(
-            &parent,
+            parent,
             shape
         )?);
     }
@@ -105,10 +105,91 @@ impl MapShape for WatcherShape {
     }
 }
 
+#[derive(Debug,Deserialize)]
+pub struct SchedulerShape {
+    pub enable: Option<bool>,
+    pub sync_interval: Option<u64>,
+    pub concurrency: Option<usize>,
+    pub purge_after: Option<u64>
+}
+
+impl MapShape for SchedulerShape {
+    fn map_shape(&mut self, rhs: Self) {
+        self.enable.map_shape(rhs.enable);
+        self.sync_interval.map_shape(rhs.sync_interval);
+        self.concurrency.map_shape(rhs.concurrency);
+        self.purge_after.map_shape(rhs.purge_after);
+    }
+}
+
+#[derive(Debug,Deserialize)]
+pub struct CompressionShape {
+    pub enable: Option<bool>,
+    pub min_size: Option<u64>
+}
+
+impl MapShape for CompressionShape {
+    fn map_shape(&mut self, rhs: Self) {
+        self.enable.map_shape(rhs.enable);
+        self.min_size.map_shape(rhs.min_size);
+    }
+}
+
+#[derive(Debug,Deserialize)]
+pub struct CorsShape {
+    pub enable: Option<bool>,
+    pub origins: Option<Vec<String>>,
+    pub methods: Option<Vec<String>>,
+    pub headers: Option<Vec<String>>,
+    pub credentials: Option<bool>,
+    pub max_age: Option<u64>
+}
+
+impl MapShape for CorsShape {
+    fn map_shape(&mut self, rhs: Self) {
+        self.enable.map_shape(rhs.enable);
+        self.origins.map_shape(rhs.origins);
+        self.methods.map_shape(rhs.methods);
+        self.headers.map_shape(rhs.headers);
+        self.credentials.map_shape(rhs.credentials);
+        self.max_age.map_shape(rhs.max_age);
+    }
+}
+
+#[derive(Debug,Deserialize)]
+pub struct BodyLimitShape {
+    pub max_size: Option<u64>,
+    pub idle_timeout: Option<u64>,
+    pub timeout: Option<u64>
+}
+
+impl MapShape for BodyLimitShape {
+    fn map_shape(&mut self, rhs: Self) {
+        self.max_size.map_shape(rhs.max_size);
+        self.idle_timeout.map_shape(rhs.idle_timeout);
+        self.timeout.map_shape(rhs.timeout);
+    }
+}
+
+#[derive(Debug,Deserialize)]
+pub struct BodyShape {
+    pub json: Option<BodyLimitShape>,
+    pub upload: Option<BodyLimitShape>
+}
+
+impl MapShape for BodyShape {
+    fn map_shape(&mut self, rhs: Self) {
+        assign_map_struct(&mut self.json, rhs.json);
+        assign_map_struct(&mut self.upload, rhs.upload);
+    }
+}
+
 #[derive(Debug,Deserialize)]
 pub struct BindInterfaceShape {
     pub host: String,
-    pub port: Option<u16>
+    pub port: Option<u16>,
+    // overrides conf.ssl.enable for this interface
+    pub ssl: Option<bool>
 }
 
 #[derive(Debug,Deserialize)]
@@ -146,6 +227,7 @@ pub struct StorageShape {
     pub directory: Option<PathBuf>,
     pub temporary: Option<PathBuf>,
     pub web_static: Option<PathBuf>,
+    pub quota: Option<u64>,
 
     #[serde(rename(deserialize = "static"))]
     pub static_: Option<StorageStaticShape>
@@ -156,6 +238,7 @@ impl MapShape for StorageShape {
         self.directory.map_shape(rhs.directory);
         self.temporary.map_shape(rhs.temporary);
         self.web_static.map_shape(rhs.web_static);
+        self.quota.map_shape(rhs.quota);
 
         assign_map_struct(&mut self.static_, rhs.static_);
     }
@@ -172,7 +255,7 @@ impl MapShape for SecurityShape {
     }
 }
 
-#[derive(Debug,Deserialize)]
+#[derive(Debug,Deserialize,Default)]
 pub struct ServerShape {
     pub storage: Option<StorageShape>,
     pub bind: Option<Vec<BindInterfaceShape>>,
@@ -182,6 +265,7 @@ pub struct ServerShape {
     pub backlog: Option<u32>,
     pub max_connections: Option<usize>,
     pub max_connection_rate: Option<usize>,
+    pub shutdown_timeout: Option<u64>,
 
     pub db: Option<DBShape>,
     pub email: Option<EmailShape>,
@@ -189,6 +273,10 @@ pub struct ServerShape {
     pub ssl: Option<SslShape>,
     pub template: Option<TemplateShape>,
     pub watcher: Option<WatcherShape>,
+    pub scheduler: Option<SchedulerShape>,
+    pub compression: Option<CompressionShape>,
+    pub cors: Option<CorsShape>,
+    pub body: Option<BodyShape>,
     pub security: Option<SecurityShape>,
 }
 
@@ -200,6 +288,7 @@ impl MapShape for ServerShape {
         self.backlog.map_shape(rhs.backlog);
         self.max_connections.map_shape(rhs.max_connections);
         self.max_connection_rate.map_shape(rhs.max_connection_rate);
+        self.shutdown_timeout.map_shape(rhs.shutdown_timeout);
 
         assign_map_struct(&mut self.storage, rhs.storage);
         assign_map_struct(&mut self.db, rhs.db);
@@ -208,32 +297,14 @@ impl MapShape for ServerShape {
         assign_map_struct(&mut self.ssl, rhs.ssl);
         assign_map_struct(&mut self.template, rhs.template);
         assign_map_struct(&mut self.watcher, rhs.watcher);
+        assign_map_struct(&mut self.scheduler, rhs.scheduler);
+        assign_map_struct(&mut self.compression, rhs.compression);
+        assign_map_struct(&mut self.cors, rhs.cors);
+        assign_map_struct(&mut self.body, rhs.body);
         assign_map_struct(&mut self.security, rhs.security);
     }
 }
 
-impl Default for ServerShape {
-    fn default() -> ServerShape {
-        ServerShape {
-            storage: None,
-            bind: None,
-            port: None,
-            threads: None,
-            backlog: None,
-            max_connections: None,
-            max_connection_rate: None,
-
-            db: None,
-            email: None,
-            info: None,
-            ssl: None,
-            template: None,
-            watcher: None,
-            security: None
-        }
-    }
-}
-
 impl TryFrom<&PathBuf> for ServerShape {
     type Error = error::Error;
 
@@ -268,7 +339,7 @@ fn validate_path_buf(conf_dir: &Path, name: &str, is_dir: bool, directory: PathB
     let to_canonicalize = if directory.has_root() {
         directory
     } else {
-        let mut with_root = conf_dir.clone().to_owned();
+        let mut with_root = conf_dir.to_owned();
         with_root.push(directory);
         with_root
     };
@@ -382,6 +453,24 @@ pub fn validate_server_shape(conf_dir: &Path, mut conf: ServerShape) -> error::R
         None
     };
 
+    conf.ssl = if let Some(mut ssl) = conf.ssl {
+        ssl.key = if let Some(key) = ssl.key {
+            Some(validate_path_buf(conf_dir, "ssl key file (conf.ssl.key)", false, key)?)
+        } else {
+            None
+        };
+
+        ssl.cert = if let Some(cert) = ssl.cert {
+            Some(validate_path_buf(conf_dir, "ssl cert file (conf.ssl.cert)", false, cert)?)
+        } else {
+            None
+        };
+
+        Some(ssl)
+    } else {
+        None
+    };
+
     conf.template = if let Some(mut template) = conf.template {
         template.directory = if let Some(directory) = template.directory {
             Some(validate_path_buf(conf_dir, "templates directory (conf.template.directory)", true, directory)?)
@@ -1,5 +1,5 @@
 use std::marker::{Sync};
-use std::convert::{From, Into};
+use std::convert::From;
 
 use tokio_postgres::types::{ToSql};
 
@@ -59,10 +59,10 @@ impl<'a> From<&[&'a(dyn ToSql + Sync)]> for QueryParams<'a> {
     
 }
 
-impl<'a> Into<Vec<&'a(dyn ToSql + Sync)>> for QueryParams<'a> {
+impl<'a> From<QueryParams<'a>> for Vec<&'a(dyn ToSql + Sync)> {
 
-    fn into(self) -> Vec<&'a(dyn ToSql + Sync)> {
-        self.params
+    fn from(val: QueryParams<'a>) -> Self {
+        val.params
     }
     
 }
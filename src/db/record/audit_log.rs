@@ -0,0 +1,181 @@
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use tokio_postgres::{GenericClient, Row};
+use uuid::Uuid;
+
+use crate::{
+    http::error::Result,
+    db::query::QueryParams
+};
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub created: DateTime<Utc>,
+    pub users_id: Option<i64>,
+    pub action: String,
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub target_type: Option<String>,
+    pub target_ids: Vec<String>,
+    pub details: JsonValue
+}
+
+impl From<&Row> for AuditEntry {
+    fn from(row: &Row) -> Self {
+        AuditEntry {
+            id: row.get(0),
+            created: row.get(1),
+            users_id: row.get(2),
+            action: row.get(3),
+            ip: row.get(4),
+            user_agent: row.get(5),
+            target_type: row.get(6),
+            target_ids: row.get(7),
+            details: row.get(8)
+        }
+    }
+}
+
+// filters for searching the audit log. all given filters must match
+pub struct AuditQuery {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub users_id: Option<i64>,
+    pub action: Option<String>,
+    pub target_id: Option<String>
+}
+
+impl AuditQuery {
+    fn where_clause<'a>(&'a self, params: &mut QueryParams<'a>) -> String {
+        let mut clauses = Vec::with_capacity(5);
+
+        if let Some(start) = self.start.as_ref() {
+            clauses.push(format!("created >= ${}", params.push(start)));
+        }
+
+        if let Some(end) = self.end.as_ref() {
+            clauses.push(format!("created < ${}", params.push(end)));
+        }
+
+        if let Some(users_id) = self.users_id.as_ref() {
+            clauses.push(format!("users_id = ${}", params.push(users_id)));
+        }
+
+        if let Some(action) = self.action.as_ref() {
+            clauses.push(format!("action = ${}", params.push(action)));
+        }
+
+        if let Some(target_id) = self.target_id.as_ref() {
+            clauses.push(format!("${} = any(target_ids)", params.push(target_id)));
+        }
+
+        if clauses.is_empty() {
+            String::new()
+        } else {
+            format!("where {}", clauses.join(" and "))
+        }
+    }
+}
+
+impl AuditEntry {
+
+    pub fn new(users_id: Option<i64>, action: &str) -> Self {
+        AuditEntry {
+            id: Uuid::new_v4(),
+            created: Utc::now(),
+            users_id,
+            action: action.to_owned(),
+            ip: None,
+            user_agent: None,
+            target_type: None,
+            target_ids: Vec::new(),
+            details: json!({})
+        }
+    }
+
+    pub fn set_target<I, T>(&mut self, target_type: &str, ids: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: ToString
+    {
+        self.target_type = Some(target_type.to_owned());
+        self.target_ids = ids.into_iter().map(|v| v.to_string()).collect();
+    }
+
+    pub async fn count(conn: &impl GenericClient, query: &AuditQuery) -> Result<i64> {
+        let mut params = QueryParams::with_capacity(5);
+        let sql = format!(
+            "select count(*) from audit_log {}",
+            query.where_clause(&mut params)
+        );
+
+        Ok(conn.query_one(sql.as_str(), params.slice()).await?.get(0))
+    }
+
+    pub async fn find(conn: &impl GenericClient, query: &AuditQuery, limit: &i64, offset: &i64) -> Result<Vec<AuditEntry>> {
+        let mut params = QueryParams::with_capacity(7);
+        let where_clause = query.where_clause(&mut params);
+        let limit_index = params.push(limit);
+        let offset_index = params.push(offset);
+        let sql = format!(
+            "\
+            select id, \
+                   created, \
+                   users_id, \
+                   action, \
+                   ip, \
+                   user_agent, \
+                   target_type, \
+                   target_ids, \
+                   details \
+            from audit_log \
+            {} \
+            order by created desc \
+            limit ${} offset ${}",
+            where_clause,
+            limit_index,
+            offset_index
+        );
+
+        Ok(conn.query(sql.as_str(), params.slice())
+            .await?
+            .iter()
+            .map(|row| row.into())
+            .collect())
+    }
+
+    pub async fn insert(&self, conn: &impl GenericClient) -> Result<()> {
+        conn.execute(
+            "\
+            insert into audit_log ( \
+                id, \
+                created, \
+                users_id, \
+                action, \
+                ip, \
+                user_agent, \
+                target_type, \
+                target_ids, \
+                details \
+            ) values \
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            &[
+                &self.id,
+                &self.created,
+                &self.users_id,
+                &self.action,
+                &self.ip,
+                &self.user_agent,
+                &self.target_type,
+                &self.target_ids,
+                &self.details
+            ]
+        ).await?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
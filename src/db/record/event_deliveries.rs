@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio_postgres::{GenericClient, Row};
+use uuid::Uuid;
+
+use crate::http::error::Result;
+
+#[repr(i16)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending = 1,
+    Delivered = 2,
+    // max attempts were reached and the delivery will not be tried again
+    // unless requested
+    Dead = 3,
+}
+
+impl From<i16> for DeliveryStatus {
+    fn from(v: i16) -> Self {
+        match v {
+            2 => DeliveryStatus::Delivered,
+            3 => DeliveryStatus::Dead,
+            _ => DeliveryStatus::Pending
+        }
+    }
+}
+
+impl From<DeliveryStatus> for i16 {
+    fn from(v: DeliveryStatus) -> Self {
+        v as i16
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventDelivery {
+    pub id: Uuid,
+    pub listener_id: Option<Uuid>,
+    pub endpoint: String,
+    pub event_name: String,
+
+    // the serialized json that is sent to the endpoint
+    pub payload: String,
+
+    pub status: DeliveryStatus,
+    pub attempts: i16,
+    pub next_attempt: DateTime<Utc>,
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub last_status: Option<i16>,
+    pub last_error: Option<String>,
+    pub created: DateTime<Utc>,
+    pub users_id: i64
+}
+
+impl From<&Row> for EventDelivery {
+    fn from(row: &Row) -> Self {
+        EventDelivery {
+            id: row.get(0),
+            listener_id: row.get(1),
+            endpoint: row.get(2),
+            event_name: row.get(3),
+            payload: row.get(4),
+            status: row.get::<usize, i16>(5).into(),
+            attempts: row.get(6),
+            next_attempt: row.get(7),
+            last_attempt: row.get(8),
+            last_status: row.get(9),
+            last_error: row.get(10),
+            created: row.get(11),
+            users_id: row.get(12)
+        }
+    }
+}
+
+impl EventDelivery {
+
+    pub fn new(listener_id: Uuid, endpoint: String, event_name: String, payload: String, users_id: i64) -> Self {
+        let now = Utc::now();
+
+        EventDelivery {
+            id: Uuid::new_v4(),
+            listener_id: Some(listener_id),
+            endpoint,
+            event_name,
+            payload,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt: now,
+            last_attempt: None,
+            last_status: None,
+            last_error: None,
+            created: now,
+            users_id
+        }
+    }
+
+    pub async fn find_listener_id(conn: &impl GenericClient, listener_id: &Uuid, limit: &i64, offset: &i64) -> Result<Vec<EventDelivery>> {
+        Ok(conn.query(
+            "\
+            select id, \
+                   listener_id, \
+                   endpoint, \
+                   event_name, \
+                   payload, \
+                   status, \
+                   attempts, \
+                   next_attempt, \
+                   last_attempt, \
+                   last_status, \
+                   last_error, \
+                   created, \
+                   users_id \
+            from event_deliveries \
+            where listener_id = $1 \
+            order by created desc \
+            limit $2 \
+            offset $3",
+            &[listener_id, limit, offset]
+        ).await?
+            .iter()
+            .map(|row| row.into())
+            .collect())
+    }
+
+    pub async fn count_listener_id(conn: &impl GenericClient, listener_id: &Uuid) -> Result<i64> {
+        let record = conn.query_one(
+            "select count(*) from event_deliveries where listener_id = $1",
+            &[listener_id]
+        ).await?;
+
+        Ok(record.get(0))
+    }
+
+    pub async fn insert(&self, conn: &impl GenericClient) -> Result<()> {
+        let status: i16 = self.status.clone().into();
+
+        conn.execute(
+            "\
+            insert into event_deliveries (\
+                id, \
+                listener_id, \
+                endpoint, \
+                event_name, \
+                payload, \
+                status, \
+                attempts, \
+                next_attempt, \
+                created, \
+                users_id\
+            ) values \
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+            &[
+                &self.id,
+                &self.listener_id,
+                &self.endpoint,
+                &self.event_name,
+                &self.payload,
+                &status,
+                &self.attempts,
+                &self.next_attempt,
+                &self.created,
+                &self.users_id
+            ]
+        ).await?;
+
+        Ok(())
+    }
+}
\ No newline at end of file
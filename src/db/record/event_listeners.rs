@@ -1,17 +1,106 @@
 use serde::{Deserialize, Serialize};
-use tokio_postgres::GenericClient;
+use tokio_postgres::{GenericClient, Row};
 use uuid::Uuid;
 
-use crate::http::error;
+use crate::{
+    http::error,
+    db::record::{FsItem, FsItemType}
+};
+
+// options for limiting which fs_items will trigger a listener
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ListenerFilter {
+    // when false only the referenced item and its direct children are
+    // considered
+    pub recursive: bool,
+    pub basename_pattern: Option<String>,
+    pub item_type: Option<FsItemType>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>
+}
+
+impl Default for ListenerFilter {
+    fn default() -> Self {
+        ListenerFilter {
+            recursive: true,
+            basename_pattern: None,
+            item_type: None,
+            min_size: None,
+            max_size: None
+        }
+    }
+}
+
+impl ListenerFilter {
+
+    // reads the filter columns starting at the given index. expects the
+    // order of recursive, basename_pattern, item_type, min_size, max_size
+    pub fn from_row(row: &Row, start: usize) -> Self {
+        ListenerFilter {
+            recursive: row.get(start),
+            basename_pattern: row.get(start + 1),
+            item_type: row.get::<usize, Option<i16>>(start + 2).map(|v| v.into()),
+            min_size: row.get(start + 3),
+            max_size: row.get(start + 4)
+        }
+    }
+
+    // depth is the distance from the referenced item to the given item with
+    // 0 being the item itself
+    pub fn matches(&self, depth: i32, item: &FsItem) -> bool {
+        if !self.recursive && depth > 1 {
+            return false;
+        }
+
+        if let Some(item_type) = self.item_type.as_ref() {
+            if *item_type != item.item_type {
+                return false;
+            }
+        }
+
+        if let Some(min) = self.min_size {
+            if item.item_size < min {
+                return false;
+            }
+        }
+
+        if let Some(max) = self.max_size {
+            if item.item_size > max {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = self.basename_pattern.as_ref() {
+            // patterns are checked before they are saved so an invalid
+            // pattern here is treated as not matching
+            match lib::glob::Pattern::new(pattern) {
+                Ok(compiled) => compiled.matches(&item.basename),
+                Err(_) => false
+            }
+        } else {
+            true
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct EventListener {
     pub id: Uuid,
-    pub event_name: String,
+    pub event_name: Vec<String>,
     pub endpoint: String,
+
+    // only sent to the user when the listener is created or the secret is
+    // rotated
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+
     pub ref_table: String,
     pub ref_id: i64,
-    pub users_id: i64
+    pub users_id: i64,
+
+    #[serde(flatten)]
+    pub filter: ListenerFilter
 }
 
 impl EventListener {
@@ -22,9 +111,15 @@ impl EventListener {
             select id, \
                    event_name, \
                    endpoint, \
+                   secret, \
                    ref_table, \
-                   ref_id \
-            from event_listeners\
+                   ref_id, \
+                   recursive, \
+                   basename_pattern, \
+                   item_type, \
+                   min_size, \
+                   max_size \
+            from event_listeners \
             where users_id = $1",
             &[users_id]
         ).await?
@@ -33,20 +128,112 @@ impl EventListener {
                 id: v.get(0),
                 event_name: v.get(1),
                 endpoint: v.get(2),
-                ref_table: v.get(3),
-                ref_id: v.get(4),
-                users_id: users_id.clone()
+                secret: v.get(3),
+                ref_table: v.get(4),
+                ref_id: v.get(5),
+                users_id: *users_id,
+                filter: ListenerFilter::from_row(v, 6)
             })
             .collect()
         )
     }
 
+    pub async fn find_id(conn: &impl GenericClient, id: &Uuid) -> error::Result<Option<EventListener>> {
+        if let Some(record) = conn.query_opt(
+            "\
+            select event_name, \
+                   endpoint, \
+                   secret, \
+                   ref_table, \
+                   ref_id, \
+                   users_id, \
+                   recursive, \
+                   basename_pattern, \
+                   item_type, \
+                   min_size, \
+                   max_size \
+            from event_listeners \
+            where id = $1",
+            &[id]
+        ).await? {
+            Ok(Some(EventListener {
+                id: *id,
+                event_name: record.get(0),
+                endpoint: record.get(1),
+                secret: record.get(2),
+                ref_table: record.get(3),
+                ref_id: record.get(4),
+                users_id: record.get(5),
+                filter: ListenerFilter::from_row(&record, 6)
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub async fn insert(&self, conn: &impl GenericClient) -> error::Result<()> {
+        let item_type: Option<i16> = self.filter.item_type.clone().map(|v| v.into());
+
+        conn.execute(
+            "\
+            insert into event_listeners ( \
+                id, \
+                event_name, \
+                endpoint, \
+                secret, \
+                ref_table, \
+                ref_id, \
+                users_id, \
+                recursive, \
+                basename_pattern, \
+                item_type, \
+                min_size, \
+                max_size \
+            ) values \
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+            &[
+                &self.id,
+                &self.event_name,
+                &self.endpoint,
+                &self.secret,
+                &self.ref_table,
+                &self.ref_id,
+                &self.users_id,
+                &self.filter.recursive,
+                &self.filter.basename_pattern,
+                &item_type,
+                &self.filter.min_size,
+                &self.filter.max_size
+            ]
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn update(&self, conn: &impl GenericClient) -> error::Result<()> {
+        let item_type: Option<i16> = self.filter.item_type.clone().map(|v| v.into());
+
         conn.execute(
             "\
-            insert into event_listeners (id, event_name, endpoint, ref_table, ref_id, users_id) values \
-            ($1, $2, $3, $4, $5, $6)",
-            &[&self.id, &self.event_name, &self.endpoint, &self.ref_table, &self.ref_id, &self.users_id]
+            update event_listeners \
+            set event_name = $2, \
+                endpoint = $3, \
+                recursive = $4, \
+                basename_pattern = $5, \
+                item_type = $6, \
+                min_size = $7, \
+                max_size = $8 \
+            where id = $1",
+            &[
+                &self.id,
+                &self.event_name,
+                &self.endpoint,
+                &self.filter.recursive,
+                &self.filter.basename_pattern,
+                &item_type,
+                &self.filter.min_size,
+                &self.filter.max_size
+            ]
         ).await?;
 
         Ok(())
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc, serde::ts_seconds};
+use serde::Serialize;
+use tokio_postgres::{GenericClient, Transaction, Row};
+
+use crate::{
+    http::error::Result,
+    db::{query::QueryParams, record::{FsItemType, join_path}}
+};
+
+// held while sequence numbers are handed out so that changes are numbered in
+// the order they became visible
+const SEQ_LOCK_KEY: i64 = 0x6673_6368;
+
+#[derive(Debug, Serialize)]
+pub struct FsItemChange {
+    pub seq: i64,
+    pub fs_items_id: i64,
+    pub change_type: String,
+    pub item_type: FsItemType,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_path: Option<String>,
+    #[serde(with = "ts_seconds")]
+    pub created: DateTime<Utc>
+}
+
+impl From<&Row> for FsItemChange {
+    fn from(row: &Row) -> Self {
+        let prev_directory: Option<&str> = row.get(6);
+        let prev_basename: Option<&str> = row.get(7);
+
+        FsItemChange {
+            seq: row.get(0),
+            fs_items_id: row.get(1),
+            change_type: row.get(2),
+            item_type: row.get::<usize, i16>(3).into(),
+            path: join_path(row.get(4), row.get(5)),
+            prev_path: prev_directory.zip(prev_basename)
+                .map(|(directory, basename)| join_path(directory, basename)),
+            created: row.get(8)
+        }
+    }
+}
+
+impl FsItemChange {
+
+    // numbers any changes that have been committed since the last call. this
+    // has to be run in its own transaction since the lock is held until it
+    // ends
+    pub async fn assign_pending(transaction: &Transaction<'_>) -> Result<u64> {
+        transaction.execute("select pg_advisory_xact_lock($1)", &[&SEQ_LOCK_KEY]).await?;
+
+        Ok(transaction.execute(
+            "\
+            update fs_item_changes \
+            set seq = pending.next_seq \
+            from ( \
+                select ordered.id, \
+                       nextval('fs_item_changes_seq') as next_seq \
+                from ( \
+                    select id \
+                    from fs_item_changes \
+                    where seq is null \
+                    order by id \
+                ) ordered \
+            ) pending \
+            where pending.id = fs_item_changes.id",
+            &[]
+        ).await?)
+    }
+
+    pub async fn latest_seq(conn: &impl GenericClient, users_id: &i64) -> Result<i64> {
+        Ok(conn.query_one(
+            "select coalesce(max(seq), 0) from fs_item_changes where users_id = $1",
+            &[users_id]
+        ).await?.get(0))
+    }
+
+    // changes after the cursor for the given item. if path is given then
+    // anything that is or was under it is included as well
+    pub async fn find_after(
+        conn: &impl GenericClient,
+        users_id: &i64,
+        fs_items_id: &i64,
+        path: Option<&str>,
+        cursor: &i64,
+        limit: &i64
+    ) -> Result<Vec<FsItemChange>> {
+        let mut params = QueryParams::with_capacity(5);
+        let users_index = params.push(users_id);
+        let cursor_index = params.push(cursor);
+        let limit_index = params.push(limit);
+        let subtree = if let Some(path) = &path {
+            let id_index = params.push(fs_items_id);
+            let path_index = params.push(path);
+
+            format!(
+                "and ( \
+                    fs_items_id = ${0} or \
+                    directory = ${1} or \
+                    left(directory, length(${1}) + 1) = ${1} || '/' or \
+                    prev_directory = ${1} or \
+                    left(prev_directory, length(${1}) + 1) = ${1} || '/' \
+                )",
+                id_index,
+                path_index
+            )
+        } else {
+            String::new()
+        };
+        let sql = format!(
+            "\
+            select seq, \
+                   fs_items_id, \
+                   change_type, \
+                   item_type, \
+                   directory, \
+                   basename, \
+                   prev_directory, \
+                   prev_basename, \
+                   created \
+            from fs_item_changes \
+            where users_id = ${} and \
+                  seq > ${} \
+                  {} \
+            order by seq \
+            limit ${}",
+            users_index,
+            cursor_index,
+            subtree,
+            limit_index
+        );
+
+        Ok(conn.query(sql.as_str(), params.slice())
+            .await?
+            .iter()
+            .map(|row| row.into())
+            .collect())
+    }
+}
\ No newline at end of file
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc, serde::ts_seconds, serde::ts_seconds_option };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -6,6 +8,16 @@ use tokio_postgres::GenericClient;
 
 use crate::http::error::Result;
 
+// directories are stored without a leading slash and items directly under
+// the root have an empty directory
+pub fn join_path(directory: &str, basename: &str) -> String {
+    if directory.is_empty() {
+        basename.to_owned()
+    } else {
+        format!("{}/{}", directory, basename)
+    }
+}
+
 #[repr(i16)]
 #[derive(Debug, PartialEq, Clone, Serialize_repr, Deserialize_repr)]
 pub enum FsItemType {
@@ -50,6 +62,18 @@ impl From<FsItemType> for i16 {
     }
 }
 
+// recursive totals for a directory. only items that exist are counted and
+// the directory itself is not included in dir_count
+#[derive(Debug, Serialize, Clone)]
+pub struct DirStats {
+    pub id: i64,
+    pub directory: String,
+    pub basename: String,
+    pub item_size: i64,
+    pub file_count: i64,
+    pub dir_count: i64
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FsItem {
     pub id: i64,
@@ -89,7 +113,7 @@ impl FsItem {
             &[id]
         ).await? {
             Ok(Some(Self {
-                id: id.clone(),
+                id: *id,
                 item_type: record.get::<usize, i16>(0).into(),
                 parent: record.get(1),
                 users_id: record.get(2),
@@ -134,7 +158,7 @@ impl FsItem {
                 id: record.get(0),
                 item_type: record.get::<usize, i16>(1).into(),
                 parent: record.get(2),
-                users_id: users_id.clone(),
+                users_id: *users_id,
                 directory: directory.to_owned(),
                 basename: basename.to_owned(),
                 item_size: record.get(3),
@@ -155,6 +179,43 @@ impl FsItem {
         FsItem::find_user_id_directory_basename(conn, users_id, &directory, &basename).await
     }
 
+    pub async fn find_user_root(conn: &impl GenericClient, users_id: &i64) -> Result<Option<FsItem>> {
+        if let Some(record) = conn.query_opt(
+            "\
+            select id, \
+                   item_type, \
+                   parent, \
+                   directory, \
+                   basename, \
+                   item_size, \
+                   created, \
+                   modified, \
+                   item_exists, \
+                   user_data \
+            from fs_items \
+            where users_id = $1 and \
+                  is_root = true",
+            &[users_id]
+        ).await? {
+            Ok(Some(Self {
+                id: record.get(0),
+                item_type: record.get::<usize, i16>(1).into(),
+                parent: record.get(2),
+                users_id: *users_id,
+                directory: record.get(3),
+                basename: record.get(4),
+                item_size: record.get(5),
+                created: record.get(6),
+                modified: record.get(7),
+                item_exists: record.get(8),
+                user_data: record.get(9),
+                is_root: true,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub async fn find_basename_with_parent(conn: &impl GenericClient, parent: &i64, basename: &String) -> Result<Option<FsItem>> {
         if let Some(record) = conn.query_opt(
             "\
@@ -176,7 +237,7 @@ impl FsItem {
             Ok(Some(Self {
                 id: record.get(0),
                 item_type: record.get::<usize, i16>(1).into(),
-                parent: Some(parent.clone()),
+                parent: Some(*parent),
                 users_id: record.get(2),
                 item_size: record.get(3),
                 directory: record.get(4),
@@ -223,7 +284,49 @@ impl FsItem {
         .map(|row| Self {
             id: row.get(0),
             item_type: row.get::<usize, i16>(1).into(),
-            parent: parent.clone(),
+            parent: *parent,
+            users_id: row.get(3),
+            directory: row.get(4),
+            basename: row.get(5),
+            item_size: row.get(6),
+            created: row.get(7),
+            modified: row.get(8),
+            item_exists: row.get(9),
+            user_data: row.get(10),
+            is_root: row.get(11),
+        })
+        .collect())
+    }
+
+    pub async fn find_largest_files(conn: &impl GenericClient, users_id: &i64, limit: &i64) -> Result<Vec<Self>> {
+        Ok(conn.query(
+            "\
+            select id, \
+                   item_type, \
+                   parent, \
+                   users_id, \
+                   directory, \
+                   basename, \
+                   item_size, \
+                   created, \
+                   modified, \
+                   item_exists, \
+                   user_data, \
+                   is_root \
+            from fs_items \
+            where users_id = $1 and \
+                  item_type = 1 and \
+                  item_exists = true \
+            order by item_size desc, \
+                     id \
+            limit $2",
+            &[users_id, limit]
+        ).await?
+        .iter()
+        .map(|row| Self {
+            id: row.get(0),
+            item_type: row.get::<usize, i16>(1).into(),
+            parent: row.get(2),
             users_id: row.get(3),
             directory: row.get(4),
             basename: row.get(5),
@@ -239,7 +342,11 @@ impl FsItem {
 
     pub async fn update_item_exists(conn: &impl GenericClient, id: &i64, exists: bool) -> Result<()> {
         conn.execute(
-            "update fs_items set item_exists = $2 where id = $1",
+            "\
+            update fs_items \
+            set item_exists = $2, \
+                missing_since = case when $2 then null else now() end \
+            where id = $1",
             &[id, &exists]
         ).await?;
 
@@ -321,4 +428,111 @@ impl FsItem {
 
     //     Ok(())
     // }
+}
+
+impl DirStats {
+
+    pub async fn find_ids(conn: &impl GenericClient, ids: &Vec<i64>) -> Result<HashMap<i64, DirStats>> {
+        let mut rtn = HashMap::with_capacity(ids.len());
+
+        if ids.is_empty() {
+            return Ok(rtn);
+        }
+
+        let records = conn.query(
+            "\
+            with recursive dir_tree as ( \
+                select fs_root.id as root_id, \
+                       fs_root.id, \
+                       fs_root.item_type, \
+                       fs_root.item_size \
+                from fs_items fs_root \
+                where fs_root.id = any($1) and \
+                      fs_root.item_type = 2 \
+                union all \
+                select dir_tree.root_id, \
+                       fs_contents.id, \
+                       fs_contents.item_type, \
+                       fs_contents.item_size \
+                from fs_items fs_contents \
+                inner join dir_tree on dir_tree.id = fs_contents.parent \
+                where fs_contents.item_exists = true \
+            ) \
+            select fs_items.id, \
+                   fs_items.directory, \
+                   fs_items.basename, \
+                   coalesce(sum(dir_tree.item_size) filter (where dir_tree.item_type = 1), 0)::bigint, \
+                   count(*) filter (where dir_tree.item_type = 1), \
+                   count(*) filter (where dir_tree.item_type = 2 and dir_tree.id <> dir_tree.root_id) \
+            from dir_tree \
+            inner join fs_items on fs_items.id = dir_tree.root_id \
+            group by fs_items.id",
+            &[ids]
+        ).await?;
+
+        for record in records {
+            let stats = DirStats {
+                id: record.get(0),
+                directory: record.get(1),
+                basename: record.get(2),
+                item_size: record.get(3),
+                file_count: record.get(4),
+                dir_count: record.get(5)
+            };
+
+            rtn.insert(stats.id, stats);
+        }
+
+        Ok(rtn)
+    }
+
+    // finds the child directories of a directory that take up the most space.
+    // only the direct children are ranked so that every item in the subtree is
+    // visited once instead of once for every directory above it
+    pub async fn find_largest(conn: &impl GenericClient, parent: &i64, limit: &i64) -> Result<Vec<DirStats>> {
+        Ok(conn.query(
+            "\
+            with recursive dir_tree as ( \
+                select fs_root.id as root_id, \
+                       fs_root.id, \
+                       fs_root.item_type, \
+                       fs_root.item_size \
+                from fs_items fs_root \
+                where fs_root.parent = $1 and \
+                      fs_root.item_type = 2 and \
+                      fs_root.item_exists = true \
+                union all \
+                select dir_tree.root_id, \
+                       fs_contents.id, \
+                       fs_contents.item_type, \
+                       fs_contents.item_size \
+                from fs_items fs_contents \
+                inner join dir_tree on dir_tree.id = fs_contents.parent \
+                where fs_contents.item_exists = true \
+            ) \
+            select fs_items.id, \
+                   fs_items.directory, \
+                   fs_items.basename, \
+                   coalesce(sum(dir_tree.item_size) filter (where dir_tree.item_type = 1), 0)::bigint as total_size, \
+                   count(*) filter (where dir_tree.item_type = 1), \
+                   count(*) filter (where dir_tree.item_type = 2 and dir_tree.id <> dir_tree.root_id) \
+            from dir_tree \
+            inner join fs_items on fs_items.id = dir_tree.root_id \
+            group by fs_items.id \
+            order by total_size desc, \
+                     fs_items.id \
+            limit $2",
+            &[parent, limit]
+        ).await?
+        .iter()
+        .map(|record| DirStats {
+            id: record.get(0),
+            directory: record.get(1),
+            basename: record.get(2),
+            item_size: record.get(3),
+            file_count: record.get(4),
+            dir_count: record.get(5)
+        })
+        .collect())
+    }
 }
\ No newline at end of file
@@ -1,8 +1,16 @@
 mod users;
 pub use users::*;
+mod user_invites;
+pub use user_invites::*;
 mod fs_items;
 pub use fs_items::*;
 mod user_sessions;
 pub use user_sessions::*;
 mod event_listeners;
-pub use event_listeners::*;
\ No newline at end of file
+pub use event_listeners::*;
+mod event_deliveries;
+pub use event_deliveries::*;
+mod audit_log;
+pub use audit_log::*;
+mod fs_item_changes;
+pub use fs_item_changes::*;
\ No newline at end of file
@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio_postgres::GenericClient;
+
+use crate::{
+    http::error::Result,
+    db::record::UserRole
+};
+
+#[derive(Debug, Serialize)]
+pub struct UserInvite {
+    pub id: String,
+    pub issued_by: i64,
+    pub email: Option<String>,
+    pub role: UserRole,
+    pub issued: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+    pub used_by: Option<i64>,
+    pub used_on: Option<DateTime<Utc>>
+}
+
+impl UserInvite {
+
+    pub fn is_valid(&self) -> bool {
+        self.used_on.is_none() && self.expires > Utc::now()
+    }
+
+    pub async fn find_id(conn: &impl GenericClient, id: &String) -> Result<Option<UserInvite>> {
+        if let Some(record) = conn.query_opt(
+            "\
+            select issued_by, \
+                   email, \
+                   role, \
+                   issued, \
+                   expires, \
+                   used_by, \
+                   used_on \
+            from user_invites \
+            where id = $1",
+            &[id]
+        ).await? {
+            Ok(Some(UserInvite {
+                id: id.clone(),
+                issued_by: record.get(0),
+                email: record.get(1),
+                role: record.get::<usize, i16>(2).into(),
+                issued: record.get(3),
+                expires: record.get(4),
+                used_by: record.get(5),
+                used_on: record.get(6)
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn find_all(conn: &impl GenericClient) -> Result<Vec<UserInvite>> {
+        Ok(conn.query(
+            "\
+            select id, \
+                   issued_by, \
+                   email, \
+                   role, \
+                   issued, \
+                   expires, \
+                   used_by, \
+                   used_on \
+            from user_invites \
+            order by issued desc",
+            &[]
+        ).await?
+            .iter()
+            .map(|record| UserInvite {
+                id: record.get(0),
+                issued_by: record.get(1),
+                email: record.get(2),
+                role: record.get::<usize, i16>(3).into(),
+                issued: record.get(4),
+                expires: record.get(5),
+                used_by: record.get(6),
+                used_on: record.get(7)
+            })
+            .collect())
+    }
+
+    pub async fn insert(&self, conn: &impl GenericClient) -> Result<()> {
+        let role: i16 = self.role.clone().into();
+
+        conn.execute(
+            "\
+            insert into user_invites (id, issued_by, email, role, issued, expires) values \
+            ($1, $2, $3, $4, $5, $6)",
+            &[&self.id, &self.issued_by, &self.email, &role, &self.issued, &self.expires]
+        ).await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_used(&mut self, conn: &impl GenericClient, users_id: &i64) -> Result<()> {
+        let used_on = Utc::now();
+
+        conn.execute(
+            "update user_invites set used_by = $2, used_on = $3 where id = $1",
+            &[&self.id, users_id, &used_on]
+        ).await?;
+
+        self.used_by = Some(*users_id);
+        self.used_on = Some(used_on);
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -25,8 +25,8 @@ impl UserSession {
 
     pub fn new(users_id: i64, duration: &Duration) -> Result<UserSession> {
         let issued_on = Utc::now();
-        let expires = issued_on.clone()
-            .checked_add_signed(duration.clone())
+        let expires = issued_on
+            .checked_add_signed(*duration)
             .ok_or(Error::default())?;
 
         Ok(UserSession {
@@ -51,7 +51,7 @@ impl UserSession {
         ).await? {
             Ok(Some(UserSession {
                 users_id: record.get(0),
-                token: token.clone(),
+                token: *token,
                 dropped: record.get(1),
                 issued_on: record.get(2),
                 expires: record.get(3)
@@ -82,7 +82,7 @@ impl UserSession {
             .await?
             .iter()
             .map(|row| UserSession {
-                users_id: users_id.clone(),
+                users_id: *users_id,
                 token: row.get(0),
                 dropped: row.get(1),
                 issued_on: row.get(2),
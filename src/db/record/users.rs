@@ -1,12 +1,12 @@
 use std::convert::TryFrom;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tokio_postgres::GenericClient;
 
 use crate::http::error::Result;
 
 #[derive(Debug)]
-pub struct InvalidAlgorithm(i16);
+pub struct InvalidAlgorithm(#[allow(dead_code)] i16);
 
 #[repr(i16)]
 #[derive(Debug, Clone)]
@@ -29,6 +29,29 @@ impl TryFrom<i16> for TotpAlgorithm {
     }
 }
 
+#[repr(i16)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    User = 1,
+    Admin = 2,
+}
+
+impl From<i16> for UserRole {
+    fn from(v: i16) -> Self {
+        match v {
+            2 => UserRole::Admin,
+            _ => UserRole::User
+        }
+    }
+}
+
+impl From<UserRole> for i16 {
+    fn from(v: UserRole) -> Self {
+        v as i16
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct User {
     pub id: i64,
@@ -48,11 +71,21 @@ pub struct User {
     #[serde(skip_serializing)]
     pub totp_step: Option<u16>,
     #[serde(skip_serializing)]
-    pub totp_digits: Option<u16>
+    pub totp_digits: Option<u16>,
+
+    pub role: UserRole,
+
+    // the storage quota for this user in bytes. none will use the default
+    // quota from the storage config
+    pub quota: Option<i64>
 }
 
 impl User {
 
+    pub fn is_admin(&self) -> bool {
+        self.role == UserRole::Admin
+    }
+
     pub async fn find_username(conn: &impl GenericClient, username: &String) -> Result<Option<User>> {
         if let Some(record) = conn.query_opt(
             "\
@@ -64,7 +97,9 @@ impl User {
                    totp_algorithm, \
                    totp_secret, \
                    totp_step, \
-                   totp_digits \
+                   totp_digits, \
+                   role, \
+                   quota \
             from users \
             where username = $1",
             &[username]
@@ -87,6 +122,8 @@ impl User {
                 totp_secret: record.get(6),
                 totp_step,
                 totp_digits,
+                role: record.get::<usize, i16>(9).into(),
+                quota: record.get(10)
             }))
         } else {
             Ok(None)
@@ -104,7 +141,9 @@ impl User {
                    totp_algorithm, \
                    totp_secret, \
                    totp_step, \
-                   totp_digits \
+                   totp_digits, \
+                   role, \
+                   quota \
             from users \
             where id = $1",
             &[id]
@@ -117,7 +156,7 @@ impl User {
                 .map(|v| u16::try_from(v).unwrap());
 
             Ok(Some(User {
-                id: id.clone(),
+                id: *id,
                 username: record.get(0),
                 hash: record.get(1),
                 email: record.get(2),
@@ -127,6 +166,8 @@ impl User {
                 totp_secret: record.get(6),
                 totp_step,
                 totp_digits,
+                role: record.get::<usize, i16>(9).into(),
+                quota: record.get(10)
             }))
         } else {
             Ok(None)
@@ -145,13 +186,15 @@ impl User {
                    totp_algorithm, \
                    totp_secret, \
                    totp_step, \
-                   totp_digits \
+                   totp_digits, \
+                   role, \
+                   quota \
             from users \
             where username = $1 or \
                   email = $2",
             &[username, email]
         ).await?.into_iter().map(|record| {
-            let totp_algorithm = record.get::<usize, Option<i16>>(5)
+            let totp_algorithm = record.get::<usize, Option<i16>>(6)
                 .map(|v| TotpAlgorithm::try_from(v).unwrap());
             let totp_step = record.get::<usize, Option<i16>>(8)
                 .map(|v| u16::try_from(v).unwrap());
@@ -168,8 +211,74 @@ impl User {
                 totp_algorithm,
                 totp_secret: record.get(7),
                 totp_step,
-                totp_digits
+                totp_digits,
+                role: record.get::<usize, i16>(10).into(),
+                quota: record.get(11)
             }
         }).collect())
     }
+
+    pub async fn find_list(conn: &impl GenericClient, limit: &i64, offset: &i64) -> Result<Vec<User>> {
+        Ok(conn.query(
+            "\
+            select id, \
+                   username, \
+                   hash, \
+                   email, \
+                   email_verified, \
+                   totp_enabled, \
+                   totp_algorithm, \
+                   totp_secret, \
+                   totp_step, \
+                   totp_digits, \
+                   role, \
+                   quota \
+            from users \
+            order by id \
+            limit $1 \
+            offset $2",
+            &[limit, offset]
+        ).await?.into_iter().map(|record| {
+            let totp_algorithm = record.get::<usize, Option<i16>>(6)
+                .map(|v| TotpAlgorithm::try_from(v).unwrap());
+            let totp_step = record.get::<usize, Option<i16>>(8)
+                .map(|v| u16::try_from(v).unwrap());
+            let totp_digits = record.get::<usize, Option<i16>>(9)
+                .map(|v| u16::try_from(v).unwrap());
+
+            User {
+                id: record.get(0),
+                username: record.get(1),
+                hash: record.get(2),
+                email: record.get(3),
+                email_verified: record.get(4),
+                totp_enabled: record.get(5),
+                totp_algorithm,
+                totp_secret: record.get(7),
+                totp_step,
+                totp_digits,
+                role: record.get::<usize, i16>(10).into(),
+                quota: record.get(11)
+            }
+        }).collect())
+    }
+
+    pub async fn count(conn: &impl GenericClient) -> Result<i64> {
+        let record = conn.query_one(
+            "select count(*) from users",
+            &[]
+        ).await?;
+
+        Ok(record.get(0))
+    }
+
+    pub async fn count_role(conn: &impl GenericClient, role: UserRole) -> Result<i64> {
+        let role: i16 = role.into();
+        let record = conn.query_one(
+            "select count(*) from users where role = $1",
+            &[&role]
+        ).await?;
+
+        Ok(record.get(0))
+    }
 }
\ No newline at end of file
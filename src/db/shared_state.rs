@@ -1,21 +1,56 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use tokio_postgres::{NoTls, Config, Error};
 
 use crate::db::types;
+use crate::http::error;
+
+// connections are checked out with their own handle to the pool so closing
+// only has to drop the one kept here. the pool goes away once the last
+// connection has been returned
+pub struct DBPool {
+    pool: RwLock<Option<types::Pool>>
+}
+
+impl DBPool {
+    pub async fn get(&self) -> error::Result<types::PoolConn<'static>> {
+        let pool = self.pool.read().unwrap().clone();
+
+        match pool {
+            Some(pool) => Ok(pool.get_owned().await?),
+            None => Err(error::Error::new(503, "ServiceUnavailable", "the server is shutting down"))
+        }
+    }
+
+    pub fn state(&self) -> Option<bb8::State> {
+        self.pool.read().unwrap()
+            .as_ref()
+            .map(|pool| pool.state())
+    }
+
+    // stops handing out connections. idle connections are closed right away
+    // and the rest as they are returned
+    pub fn close(&self) {
+        self.pool.write().unwrap().take();
+    }
+}
 
 pub struct DBState {
-    pub pool: types::Pool
+    pub pool: DBPool
 }
 
 pub type ArcDBState = Arc<DBState>;
 
 impl DBState {
     pub async fn new(conf: Config) -> Result<ArcDBState, Error> {
+        let pool = types::Pool::builder().build(
+            types::ConnectionManager::new(conf, NoTls)
+        ).await?;
+
         Ok(Arc::new(DBState {
-            pool: types::Pool::builder().build(
-                types::ConnectionManager::new(conf, NoTls)
-            ).await?
+            pool: DBPool {
+                pool: RwLock::new(Some(pool))
+            }
         }))
     }
 }
\ No newline at end of file
@@ -0,0 +1,2 @@
+mod shared_state;
+pub use shared_state::*;
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use lettre::{
+    AsyncSmtpTransport,
+    AsyncTransport,
+    Message,
+    Tokio1Executor,
+    message::Mailbox,
+    transport::smtp::authentication::Credentials
+};
+
+use crate::{
+    config::{EmailConfig, ServerInfoConfig},
+    error,
+    http
+};
+
+pub struct EmailState {
+    transport: Option<AsyncSmtpTransport<Tokio1Executor>>,
+    from: Option<Mailbox>,
+    origin: String,
+    name: String
+}
+
+pub type ArcEmailState = Arc<EmailState>;
+
+impl EmailState {
+    pub fn new(email: EmailConfig, info: &ServerInfoConfig) -> error::Result<ArcEmailState> {
+        let mut transport = None;
+        let mut from = None;
+
+        if email.enable {
+            let relay = email.relay.ok_or(error::Error::Error(
+                "email is enabled but conf.email.relay was not specified".into()
+            ))?;
+            let from_address = email.from.ok_or(error::Error::Error(
+                "email is enabled but conf.email.from was not specified".into()
+            ))?;
+            let mut builder = AsyncSmtpTransport::<Tokio1Executor>::relay(&relay)?;
+
+            if let Some(username) = email.username {
+                builder = builder.credentials(Credentials::new(
+                    username,
+                    email.password.unwrap_or_default()
+                ));
+            }
+
+            transport = Some(builder.build());
+            from = Some(from_address.parse()?);
+        }
+
+        Ok(Arc::new(EmailState {
+            transport,
+            from,
+            origin: info.origin.clone(),
+            name: info.name.clone()
+        }))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.transport.is_some()
+    }
+
+    pub async fn send(&self, to: &str, subject: &str, body: String) -> http::error::Result<()> {
+        if let (Some(transport), Some(from)) = (self.transport.as_ref(), self.from.as_ref()) {
+            let message = Message::builder()
+                .from(from.clone())
+                .to(to.parse()?)
+                .subject(subject)
+                .body(body)?;
+
+            transport.send(message).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn send_verification(&self, to: &str, key_id: &str) -> http::error::Result<()> {
+        let link = format!("{}/auth/verify?key={}", self.origin, key_id);
+        let subject = format!("{} email verification", self.name);
+        let body = format!(
+            "to verify your email for {} follow the link below.\n\n{}\n",
+            self.name,
+            link
+        );
+
+        self.send(to, &subject, body).await
+    }
+}
\ No newline at end of file
@@ -6,6 +6,7 @@ use lib::snowflake::Error as SnowflakeError;
 use crate::config::error as config_error;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     Error(String),
 
@@ -14,8 +15,12 @@ pub enum Error {
     IOError(std::io::Error),
 
     HyperError(hyper::Error),
+    TlsError(tokio_rustls::rustls::Error),
     NotifyError(notify::Error),
     PostgresError(tokio_postgres::Error),
+
+    SmtpError(lettre::transport::smtp::Error),
+    AddressError(lettre::address::AddressError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -28,8 +33,11 @@ impl fmt::Display for Error {
             Error::SnowflakeError(err) => write!(f, "{}", err),
             Error::IOError(err) => write!(f, "{:?}", err),
             Error::HyperError(err) => write!(f, "{:?}", err),
+            Error::TlsError(err) => write!(f, "{}", err),
             Error::NotifyError(err) => write!(f, "{:?}", err),
             Error::PostgresError(err) => write!(f, "{:?}", err),
+            Error::SmtpError(err) => write!(f, "{:?}", err),
+            Error::AddressError(err) => write!(f, "{:?}", err),
             // _ => write!(f, "application error")
         }
     }
@@ -42,8 +50,11 @@ impl error::Error for Error {
             Error::SnowflakeError(err) => Some(err),
             Error::IOError(err) => Some(err),
             Error::HyperError(err) => Some(err),
+            Error::TlsError(err) => Some(err),
             Error::NotifyError(err) => Some(err),
             Error::PostgresError(err) => Some(err),
+            Error::SmtpError(err) => Some(err),
+            Error::AddressError(err) => Some(err),
             _ => None
         }
     }
@@ -73,6 +84,12 @@ impl From<hyper::Error> for Error {
     }
 }
 
+impl From<tokio_rustls::rustls::Error> for Error {
+    fn from(error: tokio_rustls::rustls::Error) -> Error {
+        Error::TlsError(error)
+    }
+}
+
 impl From<notify::Error> for Error {
     fn from(error: notify::Error) -> Error {
         Error::NotifyError(error)
@@ -83,4 +100,16 @@ impl From<tokio_postgres::Error> for Error {
     fn from(error: tokio_postgres::Error) -> Error {
         Error::PostgresError(error)
     }
+}
+
+impl From<lettre::transport::smtp::Error> for Error {
+    fn from(error: lettre::transport::smtp::Error) -> Error {
+        Error::SmtpError(error)
+    }
+}
+
+impl From<lettre::address::AddressError> for Error {
+    fn from(error: lettre::address::AddressError) -> Error {
+        Error::AddressError(error)
+    }
 }
\ No newline at end of file
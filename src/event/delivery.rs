@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::{stream::FuturesUnordered, StreamExt};
+use lib::webhook::{sign, SIGNATURE_HEADER, TIMESTAMP_HEADER};
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use crate::{
+    db::record::DeliveryStatus,
+    http::error::Result,
+    state::AppState
+};
+
+const BATCH_SIZE: i64 = 25;
+const MAX_ATTEMPTS: i16 = 8;
+const BASE_BACKOFF_SECS: i64 = 10;
+const MAX_BACKOFF_SECS: i64 = 60 * 60;
+// how long a claimed delivery is held before another worker can pick it up
+const LEASE_SECS: i64 = 60;
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+struct Claimed {
+    id: Uuid,
+    endpoint: String,
+    payload: String,
+    secret: String,
+    attempts: i16
+}
+
+enum Outcome {
+    Success(i16),
+    Failed(Option<i16>, String)
+}
+
+fn backoff_secs(attempts: i16) -> i64 {
+    let exp = (attempts.max(1) - 1).min(16) as u32;
+
+    (BASE_BACKOFF_SECS * 2i64.pow(exp)).min(MAX_BACKOFF_SECS)
+}
+
+async fn send(state: &AppState, claimed: &Claimed) -> Outcome {
+    let timestamp = Utc::now().timestamp();
+    let signature = sign(claimed.secret.as_bytes(), timestamp, claimed.payload.as_bytes());
+
+    let result = state.events.client.post(&claimed.endpoint)
+        .header("content-type", "application/json")
+        .header(TIMESTAMP_HEADER, timestamp)
+        .header(SIGNATURE_HEADER, signature)
+        .body(claimed.payload.clone())
+        .send()
+        .await;
+
+    match result {
+        Ok(res) => {
+            let status = res.status();
+
+            if status.is_success() {
+                Outcome::Success(status.as_u16() as i16)
+            } else {
+                Outcome::Failed(Some(status.as_u16() as i16), format!("endpoint responded with {}", status))
+            }
+        },
+        Err(err) => Outcome::Failed(None, err.to_string())
+    }
+}
+
+async fn record_outcome(state: &AppState, claimed: Claimed, outcome: Outcome) -> Result<()> {
+    let conn = state.db.pool.get().await?;
+    let now = Utc::now();
+    let attempts = claimed.attempts + 1;
+
+    match outcome {
+        Outcome::Success(code) => {
+            let status: i16 = DeliveryStatus::Delivered.into();
+
+            conn.execute(
+                "\
+                update event_deliveries \
+                set status = $2, \
+                    attempts = $3, \
+                    last_attempt = $4, \
+                    last_status = $5, \
+                    last_error = null \
+                where id = $1",
+                &[&claimed.id, &status, &attempts, &now, &code]
+            ).await?;
+        },
+        Outcome::Failed(code, error) => {
+            let status: i16 = if attempts >= MAX_ATTEMPTS {
+                log::warn!("delivery {} to {} reached max attempts", claimed.id, claimed.endpoint);
+
+                DeliveryStatus::Dead.into()
+            } else {
+                DeliveryStatus::Pending.into()
+            };
+            let next_attempt: DateTime<Utc> = now + chrono::Duration::seconds(backoff_secs(attempts));
+
+            conn.execute(
+                "\
+                update event_deliveries \
+                set status = $2, \
+                    attempts = $3, \
+                    last_attempt = $4, \
+                    last_status = $5, \
+                    last_error = $6, \
+                    next_attempt = $7 \
+                where id = $1",
+                &[&claimed.id, &status, &attempts, &now, &code, &error, &next_attempt]
+            ).await?;
+        }
+    }
+
+    Ok(())
+}
+
+// deliveries lose their listener when it is deleted. there is nothing to
+// sign them with anymore so they are dead lettered instead of being sent
+async fn drop_orphaned(state: &AppState) -> Result<u64> {
+    let conn = state.db.pool.get().await?;
+    let pending: i16 = DeliveryStatus::Pending.into();
+    let dead: i16 = DeliveryStatus::Dead.into();
+
+    Ok(conn.execute(
+        "\
+        update event_deliveries \
+        set status = $2, \
+            last_error = 'the listener was deleted' \
+        where status = $1 and \
+              listener_id is null",
+        &[&pending, &dead]
+    ).await?)
+}
+
+// claims a batch of due deliveries by pushing their next attempt forward so
+// that nothing else will pick them up while they are being sent. deliveries
+// are signed with the current secret of the listener
+async fn claim_batch(state: &AppState) -> Result<Vec<Claimed>> {
+    let conn = state.db.pool.get().await?;
+    let now = Utc::now();
+    let lease = now + chrono::Duration::seconds(LEASE_SECS);
+    let pending: i16 = DeliveryStatus::Pending.into();
+
+    Ok(conn.query(
+        "\
+        update event_deliveries \
+        set next_attempt = $1 \
+        from event_listeners \
+        where event_listeners.id = event_deliveries.listener_id and \
+              event_deliveries.id in ( \
+                  select id \
+                  from event_deliveries \
+                  where status = $2 and \
+                        next_attempt <= $3 and \
+                        listener_id is not null \
+                  order by next_attempt \
+                  limit $4 \
+                  for update skip locked \
+              ) \
+        returning event_deliveries.id, \
+                  event_deliveries.endpoint, \
+                  event_deliveries.payload, \
+                  event_listeners.secret, \
+                  event_deliveries.attempts",
+        &[&lease, &pending, &now, &BATCH_SIZE]
+    ).await?
+        .iter()
+        .map(|row| Claimed {
+            id: row.get(0),
+            endpoint: row.get(1),
+            payload: row.get(2),
+            secret: row.get(3),
+            attempts: row.get(4)
+        })
+        .collect())
+}
+
+async fn process_batch(state: &AppState) -> Result<usize> {
+    let dropped = drop_orphaned(state).await?;
+
+    if dropped > 0 {
+        log::info!("dead lettered {} deliveries for deleted listeners", dropped);
+    }
+
+    let batch = claim_batch(state).await?;
+    let count = batch.len();
+    let mut outbound = FuturesUnordered::new();
+
+    for claimed in batch {
+        outbound.push(async move {
+            let outcome = send(state, &claimed).await;
+
+            record_outcome(state, claimed, outcome).await
+        });
+    }
+
+    while let Some(result) = outbound.next().await {
+        if let Err(err) = result {
+            log::error!("failed to record delivery outcome. {}", err);
+        }
+    }
+
+    Ok(count)
+}
+
+pub async fn delivery_worker(state: AppState) {
+    // a batch that has already been claimed is sent before stopping
+    while !state.shutdown.is_triggered() {
+        match process_batch(&state).await {
+            Ok(count) => {
+                // there may be more waiting so go again
+                if count as i64 == BATCH_SIZE {
+                    continue;
+                }
+            },
+            Err(err) => {
+                log::error!("event delivery worker error. {}", err);
+            }
+        }
+
+        tokio::select! {
+            _ = state.events.notified() => {},
+            _ = sleep(POLL_INTERVAL) => {},
+            _ = state.shutdown.wait() => {}
+        }
+    }
+}
\ No newline at end of file
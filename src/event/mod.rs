@@ -0,0 +1,269 @@
+use chrono::Utc;
+use futures::Future;
+use serde::Serialize;
+use serde_json::json;
+use tokio_postgres::{GenericClient, Row};
+use uuid::Uuid;
+
+use crate::{state::AppState, db::record::{FsItem, EventDelivery, ListenerFilter}, http::error::Result};
+
+mod shared_state;
+pub use shared_state::*;
+pub mod delivery;
+
+pub mod name {
+    pub const FS_ITEM_CREATED: &str = "fs_item:created";
+    pub const FS_ITEM_UPDATED: &str = "fs_item:updated";
+    pub const FS_ITEM_DELETED: &str = "fs_item:deleted";
+    pub const FS_ITEM_SYNCED: &str = "fs_item:synced";
+}
+
+struct ListenerTarget {
+    id: Uuid,
+    endpoint: String,
+    users_id: i64,
+
+    // distance from the listener item to the event item. will be none if the
+    // listener item is being removed along with the event item
+    depth: Option<i32>,
+    filter: ListenerFilter
+}
+
+impl ListenerTarget {
+    fn matches(&self, item: &FsItem) -> bool {
+        if let Some(depth) = self.depth {
+            self.filter.matches(depth, item)
+        } else {
+            true
+        }
+    }
+}
+
+impl From<&Row> for ListenerTarget {
+    fn from(row: &Row) -> Self {
+        ListenerTarget {
+            id: row.get(0),
+            endpoint: row.get(1),
+            users_id: row.get(2),
+            depth: row.get(3),
+            filter: ListenerFilter::from_row(row, 4)
+        }
+    }
+}
+
+fn serialize_event<D>(event_name: &str, data: D) -> Result<String>
+where
+    D: Serialize
+{
+    Ok(serde_json::to_string(&json!({
+        "event": event_name,
+        "timestamp": Utc::now(),
+        "payload": data
+    }))?)
+}
+
+// creates a delivery for each of the listeners. the delivery worker will
+// handle sending them
+async fn enqueue(conn: &impl GenericClient, targets: Vec<ListenerTarget>, event_name: &str, payload: &str) -> Result<usize> {
+    for target in targets.iter() {
+        EventDelivery::new(
+            target.id,
+            target.endpoint.clone(),
+            event_name.to_owned(),
+            payload.to_owned(),
+            target.users_id
+        ).insert(conn).await?;
+    }
+
+    Ok(targets.len())
+}
+
+// finds the given item along with all of its parent directories
+async fn find_ancestors(conn: &impl GenericClient, id: &i64) -> Result<Vec<i64>> {
+    let result = conn.query(
+        "\
+        with recursive dir_tree as ( \
+            select fs_root.id, \
+                   fs_root.parent \
+            from fs_items fs_root \
+            where id = $1 \
+            union \
+            select fs_contents.id, \
+                   fs_contents.parent \
+            from fs_items fs_contents \
+            inner join dir_tree on dir_tree.parent = fs_contents.id \
+        ) \
+        select id from dir_tree",
+        &[id]
+    ).await?;
+
+    Ok(result.iter().map(|v| v.get(0)).collect())
+}
+
+// publishes the event to any local subscribers and queues deliveries for
+// any listeners of the item
+async fn dispatch(state: &AppState, conn: &impl GenericClient, item: &FsItem, event_name: &'static str, payload: String) -> Result<()> {
+    let ids = find_ancestors(conn, &item.id).await?;
+    let targets = find_fs_item_listeners(conn, item, event_name).await?;
+    let enqueued = enqueue(conn, targets, event_name, &payload).await?;
+
+    state.events.publish(BusEvent {
+        name: event_name,
+        ids,
+        payload
+    });
+
+    if enqueued > 0 {
+        state.events.notify();
+    }
+
+    Ok(())
+}
+
+async fn error_wrapper(fut: impl Future<Output = Result<()>>) -> () {
+    let result = fut.await;
+
+    if let Err(err) = result {
+        log::error!("given event failed. error: {}", err);
+    }
+}
+
+// finds the listeners attached to the given item or any of its parent
+// directories for the given event. listeners with filters that do not match
+// the item are removed
+async fn find_fs_item_listeners(conn: &impl GenericClient, item: &FsItem, event_name: &str) -> Result<Vec<ListenerTarget>> {
+    let result = conn.query(
+        "\
+        with recursive dir_tree as ( \
+            select fs_root.id, \
+                   fs_root.parent, \
+                   1 as level \
+            from fs_items fs_root \
+            where id = $1 \
+            union \
+            select fs_contents.id, \
+                   fs_contents.parent, \
+                   dir_tree.level + 1 as level \
+            from fs_items fs_contents \
+            inner join dir_tree on dir_tree.parent = fs_contents.id \
+            where fs_contents.item_type = 2 \
+        ) \
+        select event_listeners.id, \
+               event_listeners.endpoint, \
+               event_listeners.users_id, \
+               dir_tree.level - 1 as depth, \
+               event_listeners.recursive, \
+               event_listeners.basename_pattern, \
+               event_listeners.item_type, \
+               event_listeners.min_size, \
+               event_listeners.max_size \
+        from dir_tree \
+        join event_listeners on ( \
+            ref_table = 'fs_items' and \
+            ref_id = dir_tree.id and \
+            $2 = any(event_listeners.event_name) \
+        )",
+        &[&item.id, &event_name]
+    ).await?;
+
+    Ok(result.iter()
+        .map(ListenerTarget::from)
+        .filter(|target| target.matches(item))
+        .collect())
+}
+
+// the deleted records will not be available once the delete is committed so
+// this must be called in the same transaction before the records are removed.
+// this will include listeners for the parent directories of the item along
+// with any of the deleted items. the returned event should be published once
+// the transaction is committed
+pub async fn enqueue_fs_item_deleted(conn: &impl GenericClient, data: &FsItem, deleted: &Vec<i64>) -> Result<(usize, BusEvent)> {
+    let result = conn.query(
+        "\
+        with recursive dir_tree as ( \
+            select fs_root.id, \
+                   fs_root.parent, \
+                   1 as level \
+            from fs_items fs_root \
+            where id = $1 \
+            union \
+            select fs_contents.id, \
+                   fs_contents.parent, \
+                   dir_tree.level + 1 as level \
+            from fs_items fs_contents \
+            inner join dir_tree on dir_tree.parent = fs_contents.id \
+            where fs_contents.item_type = 2 \
+        ) \
+        select event_listeners.id, \
+               event_listeners.endpoint, \
+               event_listeners.users_id, \
+               dir_tree.level - 1 as depth, \
+               event_listeners.recursive, \
+               event_listeners.basename_pattern, \
+               event_listeners.item_type, \
+               event_listeners.min_size, \
+               event_listeners.max_size \
+        from event_listeners \
+        left join dir_tree on dir_tree.id = event_listeners.ref_id \
+        where ref_table = 'fs_items' and \
+              $3 = any(event_name) and \
+              (dir_tree.id is not null or ref_id = any($2))",
+        &[&data.id, deleted, &name::FS_ITEM_DELETED]
+    ).await?;
+    let targets = result.iter()
+        .map(ListenerTarget::from)
+        .filter(|target| target.matches(data))
+        .collect();
+    let payload = serialize_event(name::FS_ITEM_DELETED, json!({
+        "fs_item": data,
+        "deleted": deleted
+    }))?;
+    let enqueued = enqueue(conn, targets, name::FS_ITEM_DELETED, &payload).await?;
+
+    let mut ids = find_ancestors(conn, &data.id).await?;
+    ids.extend(deleted.iter());
+
+    Ok((enqueued, BusEvent {
+        name: name::FS_ITEM_DELETED,
+        ids,
+        payload
+    }))
+}
+
+pub fn trigger_fs_item_created(state: &AppState, data: FsItem) -> impl Future<Output = ()> {
+    let state = state.clone();
+
+    error_wrapper(async move {
+        let conn = state.db.pool.get().await?;
+        let payload = serialize_event(name::FS_ITEM_CREATED, &data)?;
+
+        dispatch(&state, &*conn, &data, name::FS_ITEM_CREATED, payload).await
+    })
+}
+
+pub fn trigger_fs_item_updated(state: &AppState, data: FsItem) -> impl Future<Output = ()> {
+    let state = state.clone();
+
+    error_wrapper(async move {
+        let conn = state.db.pool.get().await?;
+        let payload = serialize_event(name::FS_ITEM_UPDATED, &data)?;
+
+        dispatch(&state, &*conn, &data, name::FS_ITEM_UPDATED, payload).await
+    })
+}
+
+pub fn trigger_fs_item_synced(state: &AppState, data: FsItem, created: u64, updated: u64, missing: u64) -> impl Future<Output = ()> {
+    let state = state.clone();
+
+    error_wrapper(async move {
+        let conn = state.db.pool.get().await?;
+        let payload = serialize_event(name::FS_ITEM_SYNCED, json!({
+            "fs_item": &data,
+            "created": created,
+            "updated": updated,
+            "missing": missing
+        }))?;
+
+        dispatch(&state, &*conn, &data, name::FS_ITEM_SYNCED, payload).await
+    })
+}
\ No newline at end of file
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Notify, broadcast};
+
+use crate::error;
+
+// how many events a subscriber can fall behind before it starts to miss them
+const BUS_CAPACITY: usize = 1024;
+
+#[derive(Debug)]
+pub struct BusEvent {
+    pub name: &'static str,
+    // the item the event is for along with its parent directories. for
+    // deletes this will also include all of the removed items
+    pub ids: Vec<i64>,
+    // the same json that is sent to listeners
+    pub payload: String
+}
+
+impl BusEvent {
+    pub fn is_within(&self, id: &i64) -> bool {
+        self.ids.contains(id)
+    }
+}
+
+pub struct EventState {
+    pub client: reqwest::Client,
+    wake: Notify,
+    bus: broadcast::Sender<Arc<BusEvent>>
+}
+
+pub type ArcEventState = Arc<EventState>;
+
+impl EventState {
+    pub fn new() -> error::Result<ArcEventState> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| error::Error::Error(format!("failed to create event client. {}", e)))?;
+
+        let (bus, _) = broadcast::channel(BUS_CAPACITY);
+
+        Ok(Arc::new(EventState {
+            client,
+            wake: Notify::new(),
+            bus
+        }))
+    }
+
+    // lets the delivery worker know that there are new deliveries available
+    pub fn notify(&self) {
+        self.wake.notify_one();
+    }
+
+    pub async fn notified(&self) {
+        self.wake.notified().await
+    }
+
+    pub fn publish(&self, event: BusEvent) {
+        // an error only means that there are no subscribers
+        let _ = self.bus.send(Arc::new(event));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<BusEvent>> {
+        self.bus.subscribe()
+    }
+}
\ No newline at end of file
@@ -1,16 +1,111 @@
+use std::fmt;
 use std::path::Path;
+use std::time::Duration;
 
 use futures::StreamExt;
-use hyper::{Body, body::Buf};
-use serde::de::DeserializeOwned;
+use hyper::{Body, body::{Buf, Bytes}};
+use serde::{Deserialize, Deserializer, de::DeserializeOwned};
 use tokio::fs::{File, OpenOptions};
 use tokio::io::AsyncWriteExt;
+use tokio::time::{Instant, timeout_at};
 
 use crate::http::{
     error::Result,
     error::Error
 };
 
+type BoxDynError = Box<dyn std::error::Error + Send + Sync>;
+
+// returned by a limited body. hyper passes it along as the source of the
+// error it gives to whatever is reading the body
+#[derive(Debug, Clone, Copy)]
+pub enum BodyError {
+    TooLarge,
+    IdleTimeout,
+    Timeout
+}
+
+impl std::error::Error for BodyError {}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::TooLarge => f.write_str("request body is too large"),
+            BodyError::IdleTimeout => f.write_str("request body idle timeout"),
+            BodyError::Timeout => f.write_str("request body timeout")
+        }
+    }
+}
+
+impl From<BodyError> for Error {
+    fn from(error: BodyError) -> Error {
+        match error {
+            BodyError::TooLarge => Error::new(413, "BodyTooLarge", "the request body is larger than allowed"),
+            BodyError::IdleTimeout => Error::new(408, "BodyTimeout", "no data was received for the request body in time"),
+            BodyError::Timeout => Error::new(408, "BodyTimeout", "the request body was not received in time")
+        }
+    }
+}
+
+// wraps the body so that reading it stops once it goes over the max size or
+// takes too long. the idle timeout is reset with every chunk received
+pub fn limit_body(body: Body, max_size: Option<u64>, idle_timeout: Option<Duration>, timeout: Option<Duration>) -> Body {
+    let deadline = timeout.map(|t| Instant::now() + t);
+
+    let stream = futures::stream::unfold(Some((body, 0u64)), move |state| async move {
+        let (mut body, mut read) = state?;
+        let idle_at = idle_timeout.map(|t| Instant::now() + t);
+        let wait_until = match (idle_at, deadline) {
+            (Some(idle), Some(total)) => Some(idle.min(total)),
+            (idle, total) => idle.or(total)
+        };
+
+        let next = if let Some(at) = wait_until {
+            match timeout_at(at, body.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    let err = if deadline == Some(at) {
+                        BodyError::Timeout
+                    } else {
+                        BodyError::IdleTimeout
+                    };
+
+                    return Some((Err(BoxDynError::from(err)), None));
+                }
+            }
+        } else {
+            body.next().await
+        };
+
+        match next? {
+            Ok(bytes) => {
+                read += bytes.len() as u64;
+
+                if let Some(max) = max_size {
+                    if read > max {
+                        return Some((Err(BoxDynError::from(BodyError::TooLarge)), None));
+                    }
+                }
+
+                Some((Ok::<Bytes, BoxDynError>(bytes), Some((body, read))))
+            },
+            Err(err) => Some((Err(BoxDynError::from(err)), None))
+        }
+    });
+
+    Body::wrap_stream(stream)
+}
+
+// allows for telling the difference between a missing field and a field set
+// to null when used with #[serde(default, deserialize_with = "...")]
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>
+{
+    Deserialize::deserialize(deserializer).map(Some)
+}
+
 pub async fn json_from_body<T>(mut body: Body) -> Result<T>
 where
     T: DeserializeOwned
@@ -46,7 +141,16 @@ where
     }
 }
 
-pub async fn file_from_body<T>(path: T, open: bool, mut body: Body) -> Result<(File, usize)>
+pub enum FileBody {
+    Written(File, usize),
+    // the body went over the limit given to file_from_body. this is separate
+    // from BodyError::TooLarge which comes from the limits on the route
+    OverLimit
+}
+
+// writes the body to the given path. if a limit is given and the body goes
+// over it the remaining body is not read
+pub async fn file_from_body<T>(path: T, open: bool, mut body: Body, limit: Option<u64>) -> Result<FileBody>
 where
     T: AsRef<Path> 
 {
@@ -54,6 +158,7 @@ where
     let mut file = OpenOptions::new()
         .write(true)
         .create(!open)
+        .truncate(true)
         .open(path)
         .await?;
 
@@ -61,10 +166,16 @@ where
         let mut bytes = chunk?;
         written += bytes.len();
 
+        if let Some(max) = limit {
+            if written as u64 > max {
+                return Ok(FileBody::OverLimit);
+            }
+        }
+
         while bytes.has_remaining() {
             file.write_buf(&mut bytes).await?;
         }
     }
 
-    Ok((file, written))
+    Ok(FileBody::Written(file, written))
 }
\ No newline at end of file
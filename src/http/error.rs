@@ -52,9 +52,7 @@ impl Error {
     where
         T: Into<BoxDynError>
     {
-        let mut rtn = Self::default();
-        rtn.source = Some(source.into());
-        rtn
+        Error { source: Some(source.into()), ..Self::default() }
     }
 
     pub fn status_ref(&self) -> &u16 {
@@ -143,6 +141,15 @@ impl From<hyper::http::Error> for Error {
 
 impl From<hyper::Error> for Error {
     fn from(error: hyper::Error) -> Self {
+        // limits on the request body are reported as errors from the body
+        let body_error = std::error::Error::source(&error)
+            .and_then(|source| source.downcast_ref::<crate::http::body::BodyError>())
+            .copied();
+
+        if let Some(body_error) = body_error {
+            return Self::from(body_error);
+        }
+
         Self::with_source(error)
     }
 }
@@ -177,6 +184,12 @@ impl From<lib::snowflake::Error> for Error {
     }
 }
 
+impl From<tokio::task::JoinError> for Error {
+    fn from(error: tokio::task::JoinError) -> Self {
+        Self::with_source(error)
+    }
+}
+
 impl From<argon2::Error> for Error {
     fn from(error: argon2::Error) -> Self {
         Self::with_source(error)
@@ -187,4 +200,22 @@ impl From<reqwest::Error> for Error {
     fn from(error: reqwest::Error) -> Self {
         Self::with_source(error)
     }
+}
+
+impl From<lettre::error::Error> for Error {
+    fn from(error: lettre::error::Error) -> Self {
+        Self::with_source(error)
+    }
+}
+
+impl From<lettre::transport::smtp::Error> for Error {
+    fn from(error: lettre::transport::smtp::Error) -> Self {
+        Self::with_source(error)
+    }
+}
+
+impl From<lettre::address::AddressError> for Error {
+    fn from(error: lettre::address::AddressError) -> Self {
+        Self::with_source(error)
+    }
 }
\ No newline at end of file
@@ -18,9 +18,5 @@ pub fn get_header<'a>(header: &'a HeaderMap, key: &str) -> Result<Option<&'a str
 }
 */
 pub fn copy_header_value(headers: &HeaderMap, key: &str) -> Option<std::result::Result<String, ToStrError>> {
-    if let Some(value) = headers.get(key) {
-        Some(value.to_str().map(|v| v.to_owned()))
-    } else {
-        None
-    }
+    headers.get(key).map(|value| value.to_str().map(|v| v.to_owned()))
 }
\ No newline at end of file
@@ -1,6 +1,5 @@
 use std::{ffi::OsStr, collections::HashMap};
 
-use hyper::HeaderMap;
 use mime::Mime;
 use lazy_static::lazy_static;
 
@@ -28,27 +27,6 @@ lazy_static! {
     };
 }
 
-use crate::http::error;
-
-pub fn get_accepting_default<S>(
-    headers: &HeaderMap, default: S
-) -> error::Result<Vec<mime::Mime>>
-where
-    S: AsRef<str>
-{
-    if let Some(accept) = headers.get("accept") {
-        let mut rtn: Vec<mime::Mime> = Vec::new();
-
-        for item in accept.to_str()?.split(",") {
-            rtn.push(item.parse()?);
-        }
-
-        Ok(rtn)
-    } else {
-        Ok(vec!(default.as_ref().parse()?))
-    }
-}
-
 pub fn mime_type_from_ext(ext: Option<&OsStr>) -> Mime {
     if let Some(ext) = ext {
         if let Some(mime_str) = EXT_MIME_MAP.get(ext) {
@@ -59,4 +37,26 @@ pub fn mime_type_from_ext(ext: Option<&OsStr>) -> Mime {
     } else {
         mime::APPLICATION_OCTET_STREAM
     }
+}
+
+// types that are worth compressing. anything else, like images or archives,
+// is usually compressed already
+pub fn is_compressible(mime: &Mime) -> bool {
+    let type_ = mime.type_();
+    let subtype = mime.subtype();
+
+    if type_ == mime::TEXT {
+        // each event needs to be sent as soon as it is written
+        subtype != mime::EVENT_STREAM
+    } else if type_ == mime::APPLICATION {
+        subtype == mime::JSON ||
+            subtype == mime::JAVASCRIPT ||
+            subtype == mime::XML ||
+            mime.suffix() == Some(mime::JSON) ||
+            mime.suffix() == Some(mime::XML)
+    } else if type_ == mime::IMAGE {
+        subtype == mime::SVG
+    } else {
+        false
+    }
 }
\ No newline at end of file
@@ -9,8 +9,7 @@ use serde::Serialize;
 use serde_json::json;
 
 use super::error;
-use super::types::{Response, Request};
-use super::mime::get_accepting_default;
+use super::types::Response;
 
 #[inline]
 pub fn build() -> Builder {
@@ -25,20 +24,6 @@ pub fn okay_text_response() -> error::Result<Response> {
         .map_err(Into::into)
 }
 
-pub fn okay_html_response() -> error::Result<Response> {
-    build()
-        .status(200)
-        .header("content-type", "text/html")
-        .body("<!DOCTYPE html>\
-        <html>\
-            <head>\
-                <title>okay</title>\
-            </head>\
-            <body>okay</body>\
-        </html>".into())
-        .map_err(Into::into)
-}
-
 pub struct JsonResponseBuilder {
     builder: Builder,
     message: String,
@@ -130,27 +115,6 @@ impl JsonResponseBuilder {
     }
 }
 
-pub fn okay_response(req: Request) -> error::Result<Response> {
-    let accept = get_accepting_default(req.headers(), "text/plain")?;
-
-    for mime in accept {
-        if mime.type_() == "text" || mime.type_() == "*" {
-            if mime.subtype() == "plain" || mime.subtype() == "*" {
-                return okay_text_response();
-            } else if mime.subtype() == "html" {
-                return okay_html_response();
-            }
-        } else if mime.type_() == "application" {
-            if mime.subtype() == "json" {
-                return JsonResponseBuilder::new(200)
-                    .response()
-            }
-        }
-    }
-
-    okay_text_response()
-}
-
 pub fn redirect_response<P>(new_path: P) -> error::Result<Response>
 where
     HeaderValue: TryFrom<P>,
@@ -1,7 +1,9 @@
-use std::{collections::HashMap, hash::Hash, borrow::Borrow};
+use std::{collections::HashMap, hash::Hash, borrow::Borrow, fmt::Display, str::FromStr};
 
 use hyper::Uri;
 
+use crate::http::error::{Result, Error};
+
 const EMPTY_QUERY: &str = "";
 
 pub fn query_iter(uri: &Uri) -> impl Iterator<Item=(&str,Option<&str>)> {
@@ -53,14 +55,9 @@ impl QueryMap {
         K: ?Sized + Hash + Eq,
         String: Borrow<K>
     {
-        if let Some(list) = self.0.get(key) {
-            Some(list.first()
-                .unwrap()
-                .as_ref()
-                .map(|v| v.clone()))
-        } else {
-            None
-        }
+        self.0.get(key).map(|list| list.first()
+            .unwrap()
+            .clone())
     }
 
     pub fn get_value_ref<K>(&self, key: &K) -> Option<&Option<String>>
@@ -75,6 +72,27 @@ impl QueryMap {
         }
     }
 
+    pub fn get_parsed<K, T>(&self, key: &K) -> Result<Option<T>>
+    where
+        K: ?Sized + Hash + Eq + Display,
+        String: Borrow<K>,
+        T: FromStr
+    {
+        if let Some(value) = self.get_value_ref(key) {
+            if let Some(value) = value {
+                if let Ok(parsed) = value.parse() {
+                    Ok(Some(parsed))
+                } else {
+                    Err(Error::new(400, "InvalidQueryValue", format!("the value given for \"{}\" is invalid", key)))
+                }
+            } else {
+                Err(Error::new(400, "MissingQueryValue", format!("no value was given for \"{}\"", key)))
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
     // pub fn get_all<K>(&self, key: K) -> Option<&Vec<Option<String>>>
     // where
     //     K: Into<String>
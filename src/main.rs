@@ -1,10 +1,12 @@
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use hyper::Server;
 use futures::future::try_join_all;
 use log::{log_enabled, Level};
 use tokio::runtime::Handle;
+use tokio::time::timeout;
 
 mod error;
 
@@ -14,15 +16,23 @@ mod http;
 mod db;
 mod storage;
 mod template;
+mod email;
 mod snowflakes;
 mod security;
+mod tls;
+mod net;
 mod state;
+mod shutdown;
 mod event;
+mod watcher;
+mod scheduler;
 
 mod components;
 
 mod routing;
 
+const POOL_CLOSE_GRACE: Duration = Duration::from_millis(250);
+
 fn main() {
     std::process::exit(match main_entry() {
         Ok(code) => code,
@@ -39,12 +49,7 @@ fn main_entry() -> error::Result<i32> {
     let mut args = std::env::args();
     args.next();
 
-    loop {
-        let arg = match args.next() {
-            Some(s) => s,
-            None => break
-        };
-
+    for arg in args {
         config_files.push(
             config::get_config_file(&arg)?
         );
@@ -70,31 +75,83 @@ fn main_entry() -> error::Result<i32> {
         .worker_threads(conf.threads)
         .build()?;
 
-    rt.block_on(main_runtime(conf, rt.handle().clone()))
+    let result = rt.block_on(main_runtime(conf, rt.handle().clone()));
+
+    // anything still running past the shutdown deadline is dropped here
+    rt.shutdown_timeout(Duration::from_secs(1));
+
+    result
 }
 
 async fn main_runtime(conf: config::ServerConfig, rt_handle: Handle) -> error::Result<i32> {
+    let shutdown_timeout = Duration::from_secs(conf.shutdown_timeout);
     let db_conf = conf.db;
     let storage_conf = conf.storage;
     let template_conf = conf.template;
+    let email_conf = conf.email;
     let state = state::AppState {
         db: db::DBState::new(db::build_config(db_conf)).await?,
         storage: storage_conf.into(),
         template: template::TemplateState::new(template::build_registry(template_conf)?),
+        email: email::EmailState::new(email_conf, &conf.info)?,
+        events: event::EventState::new()?,
         snowflakes: snowflakes::IdSnowflakes::new(1)?,
-        offload: rt_handle
+        offload: rt_handle,
+        connections: net::ConnectionState::new(
+            conf.backlog,
+            conf.max_connections,
+            conf.max_connection_rate
+        ),
+        shutdown: shutdown::ShutdownState::new()
+    };
+
+    let mut workers = Vec::new();
+
+    workers.push(tokio::spawn(event::delivery::delivery_worker(state.clone())));
+
+    if conf.watcher.enable {
+        let watcher_state = state.clone();
+
+        workers.push(tokio::spawn(async move {
+            if let Err(err) = watcher::watch(watcher_state).await {
+                log::error!("file system watcher stopped. {}", err);
+            }
+        }));
+    }
+
+    if conf.scheduler.enable {
+        workers.push(state.offload.spawn(scheduler::run(state.clone(), conf.scheduler)));
+    }
+
+    let tls_state = if conf.bind.iter().any(|bind| bind.ssl) {
+        let tls_state = tls::TlsState::new(&conf.ssl)?;
+        let reload_state = tls_state.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = tls::reload_on_hangup(reload_state).await {
+                log::error!("tls reload handler stopped. {}", err);
+            }
+        });
+
+        Some(tls_state)
+    } else {
+        None
     };
 
     let mut futures_list = Vec::new();
 
     for bind in conf.bind {
-        match bind.to_sockaddr() {
+        let use_ssl = bind.ssl;
+
+        match bind.into_sockaddr() {
             Ok(addr) => {
+                let router = routing::MakeRouter::new(state.clone(), conf.compression.clone(), &conf.cors, conf.body);
                 let fut = tokio::spawn(make_server(
                     addr,
-                    routing::MakeRouter {
-                        state: state.clone()
-                    }
+                    router,
+                    state.connections.clone(),
+                    if use_ssl { tls_state.clone() } else { None },
+                    state.shutdown.clone()
                 ));
 
                 futures_list.push(fut);
@@ -105,7 +162,55 @@ async fn main_runtime(conf: config::ServerConfig, rt_handle: Handle) -> error::R
         }
     }
 
-    try_join_all(futures_list).await.unwrap();
+    let mut servers = try_join_all(futures_list);
+
+    tokio::select! {
+        result = &mut servers => {
+            return match result {
+                Ok(_) => Ok(0),
+                Err(err) => {
+                    log::error!("server task failed. {}", err);
+
+                    Ok(1)
+                }
+            };
+        },
+        result = shutdown::wait_for_signal() => result?
+    }
+
+    log::info!("shutting down. waiting up to {}s for work to finish", shutdown_timeout.as_secs());
+
+    state.shutdown.trigger();
+
+    let drain = async {
+        // servers stop accepting right away and finish once their open
+        // connections have closed
+        if let Err(err) = servers.await {
+            log::error!("server task failed. {}", err);
+        }
+
+        for worker in workers {
+            if let Err(err) = worker.await {
+                log::error!("background worker failed. {}", err);
+            }
+        }
+
+        state.shutdown.drained().await;
+    };
+
+    if timeout(shutdown_timeout, drain).await.is_err() {
+        log::warn!("shutdown deadline reached. remaining work will be dropped");
+    } else {
+        log::info!("all work finished");
+    }
+
+    // tasks cut off at the deadline can still hold connections. those are
+    // closed when the tasks are dropped with the runtime
+    state.db.pool.close();
+
+    // gives the connection tasks a moment to tell the database they are
+    // closing before the runtime stops
+    tokio::time::sleep(POOL_CLOSE_GRACE).await;
 
     Ok(0)
 }
@@ -113,15 +218,30 @@ async fn main_runtime(conf: config::ServerConfig, rt_handle: Handle) -> error::R
 async fn make_server(
     addr: std::net::SocketAddr,
     router: routing::MakeRouter,
+    connections: net::ArcConnectionState,
+    tls_state: Option<tls::TlsState>,
+    shutdown_state: shutdown::ArcShutdownState
 ) -> error::Result<()> {
     let svc = tower::ServiceBuilder::new()
         .service(router);
 
-    match Server::try_bind(&addr) {
-        Ok(builder) => {
-            log::info!("server listening on {}", addr);
+    match net::bind(addr, connections.backlog) {
+        Ok(listener) => {
+            if tls_state.is_some() {
+                log::info!("server listening on {} with tls", addr);
+            } else {
+                log::info!("server listening on {}", addr);
+            }
+
+            let incoming = net::incoming(listener, connections, tls_state);
+
+            let server = Server::builder(incoming)
+                .serve(svc)
+                .with_graceful_shutdown(async move {
+                    shutdown_state.wait().await
+                });
 
-            if let Err(e) = builder.serve(svc).await {
+            if let Err(e) = server.await {
                 log::error!("server error. {:?}", e);
             }
         },
@@ -0,0 +1,190 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use hyper::server::accept::{Accept, from_stream};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
+use tokio::sync::{mpsc, OwnedSemaphorePermit};
+use tokio::time::{sleep, timeout};
+use tokio_rustls::server::TlsStream;
+
+use crate::tls::TlsState;
+
+mod shared_state;
+pub use shared_state::*;
+
+// how long a client has to finish the tls handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// connections that are ready but have not been picked up by the server yet
+const READY_CAPACITY: usize = 128;
+
+// how long to wait before accepting again after an error. this is usually
+// from running out of file descriptors
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+pub enum Stream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>)
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Stream::Tls(stream) => Pin::new(stream).poll_read(cx, buf)
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Stream::Tls(stream) => Pin::new(stream).poll_write(cx, buf)
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Stream::Tls(stream) => Pin::new(stream).poll_flush(cx)
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Stream::Tls(stream) => Pin::new(stream).poll_shutdown(cx)
+        }
+    }
+}
+
+// an accepted connection. counts against the connection limit until it is
+// dropped
+pub struct Connection {
+    stream: Stream,
+    remote_addr: SocketAddr,
+    state: ArcConnectionState,
+    _permit: Option<OwnedSemaphorePermit>
+}
+
+impl Connection {
+    fn new(stream: Stream, remote_addr: SocketAddr, state: ArcConnectionState, permit: Option<OwnedSemaphorePermit>) -> Connection {
+        state.opened();
+
+        Connection {
+            stream,
+            remote_addr,
+            state,
+            _permit: permit
+        }
+    }
+
+    pub fn remote_addr(&self) -> SocketAddr {
+        self.remote_addr
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.state.closed();
+    }
+}
+
+impl AsyncRead for Connection {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Connection {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+pub fn bind(addr: SocketAddr, backlog: u32) -> std::io::Result<TcpListener> {
+    let socket = if addr.is_ipv4() {
+        TcpSocket::new_v4()?
+    } else {
+        TcpSocket::new_v6()?
+    };
+
+    socket.set_reuseaddr(true)?;
+    socket.bind(addr)?;
+    socket.listen(backlog)
+}
+
+// accepts connections from the listener while staying within the limits of
+// the connection state. anything over the limits waits in the listen backlog.
+// tls handshakes are done in their own tasks so that a slow client does not
+// hold up accepting others
+pub fn incoming(
+    listener: TcpListener,
+    state: ArcConnectionState,
+    tls: Option<TlsState>
+) -> impl Accept<Conn = Connection, Error = std::io::Error> {
+    let (sender, receiver) = mpsc::channel::<std::io::Result<Connection>>(READY_CAPACITY);
+
+    tokio::spawn(async move {
+        // the receiver is dropped once the server stops accepting
+        loop {
+            let permit = tokio::select! {
+                permit = state.acquire() => permit,
+                _ = sender.closed() => break
+            };
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = sender.closed() => break
+            };
+            let (stream, remote_addr) = match accepted {
+                Ok(accepted) => accepted,
+                Err(err) => {
+                    log::error!("failed to accept connection. {:?}", err);
+
+                    sleep(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            };
+
+            if let Some(tls) = tls.as_ref() {
+                let acceptor = tls.acceptor();
+                let state = state.clone();
+                let sender = sender.clone();
+
+                tokio::spawn(async move {
+                    match timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let conn = Connection::new(Stream::Tls(Box::new(stream)), remote_addr, state, permit);
+                            let _ = sender.send(Ok(conn)).await;
+                        },
+                        Ok(Err(err)) => {
+                            log::debug!("tls handshake failed. {} {}", remote_addr, err);
+                        },
+                        Err(_) => {
+                            log::debug!("tls handshake timed out. {}", remote_addr);
+                        }
+                    }
+                });
+            } else {
+                let conn = Connection::new(Stream::Plain(stream), remote_addr, state.clone(), permit);
+                let _ = sender.send(Ok(conn)).await;
+            }
+        }
+    });
+
+    from_stream(futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|conn| (conn, receiver))
+    }))
+}
\ No newline at end of file
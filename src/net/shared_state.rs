@@ -0,0 +1,130 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::{Semaphore, OwnedSemaphorePermit};
+use tokio::time::{sleep_until, Instant};
+
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionStatus {
+    pub active: usize,
+    pub accepted: u64,
+    pub connection_limited: u64,
+    pub rate_limited: u64,
+    pub max_connections: usize,
+    pub max_connection_rate: usize,
+    pub backlog: u32
+}
+
+// shared across all listeners. a value of 0 for max_connections or
+// max_connection_rate disables that limit
+pub struct ConnectionState {
+    pub backlog: u32,
+    max_connections: usize,
+    max_connection_rate: usize,
+    permits: Option<Arc<Semaphore>>,
+    active: AtomicUsize,
+    // start of the current rate window and the connections accepted in it
+    window: Mutex<(Instant, usize)>,
+    accepted: AtomicU64,
+    connection_limited: AtomicU64,
+    rate_limited: AtomicU64
+}
+
+pub type ArcConnectionState = Arc<ConnectionState>;
+
+impl ConnectionState {
+    pub fn new(backlog: u32, max_connections: usize, max_connection_rate: usize) -> ArcConnectionState {
+        let permits = if max_connections == 0 {
+            None
+        } else {
+            Some(Arc::new(Semaphore::new(max_connections)))
+        };
+
+        Arc::new(ConnectionState {
+            backlog,
+            max_connections,
+            max_connection_rate,
+            permits,
+            active: AtomicUsize::new(0),
+            window: Mutex::new((Instant::now(), 0)),
+            accepted: AtomicU64::new(0),
+            connection_limited: AtomicU64::new(0),
+            rate_limited: AtomicU64::new(0)
+        })
+    }
+
+    // waits until another connection is allowed. the permit is held for as
+    // long as the connection is open
+    pub async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        let permit = if let Some(permits) = self.permits.as_ref() {
+            match permits.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    self.connection_limited.fetch_add(1, Ordering::Relaxed);
+
+                    // the semaphore is never closed
+                    Some(permits.clone().acquire_owned().await.unwrap())
+                }
+            }
+        } else {
+            None
+        };
+
+        if self.max_connection_rate > 0 {
+            let mut limited = false;
+
+            loop {
+                let wait_until = {
+                    let mut window = self.window.lock().unwrap();
+                    let now = Instant::now();
+
+                    if now.duration_since(window.0) >= RATE_WINDOW {
+                        *window = (now, 0);
+                    }
+
+                    if window.1 < self.max_connection_rate {
+                        window.1 += 1;
+
+                        break;
+                    }
+
+                    window.0 + RATE_WINDOW
+                };
+
+                if !limited {
+                    limited = true;
+                    self.rate_limited.fetch_add(1, Ordering::Relaxed);
+                }
+
+                sleep_until(wait_until).await;
+            }
+        }
+
+        permit
+    }
+
+    pub fn opened(&self) {
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        self.active.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn closed(&self) {
+        self.active.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> ConnectionStatus {
+        ConnectionStatus {
+            active: self.active.load(Ordering::Relaxed),
+            accepted: self.accepted.load(Ordering::Relaxed),
+            connection_limited: self.connection_limited.load(Ordering::Relaxed),
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            max_connections: self.max_connections,
+            max_connection_rate: self.max_connection_rate,
+            backlog: self.backlog
+        }
+    }
+}
\ No newline at end of file
@@ -21,11 +21,11 @@ pub async fn handle_req(state: AppState, req: Request) -> Result<Response> {
                 let mut first = true;
 
                 for value in stripped.split("/") {
-                    if value == ".." || value == "." || value.len() == 0 {
+                    if value == ".." || value == "." || value.is_empty() {
                         return Err(Error::new(
                             400, 
                             "MalformedResourcePath", 
-                            format!("resource path given contains invalid segments. \"..\", \".\", and \"\" are not allowed in the path")
+                            "resource path given contains invalid segments. \"..\", \".\", and \"\" are not allowed in the path".to_string()
                         ))
                     }
 
@@ -48,7 +48,7 @@ pub async fn handle_req(state: AppState, req: Request) -> Result<Response> {
     }
 
     if let Some(file_path) = to_send {
-        if head.method != Method::GET {
+        if head.method != Method::GET && head.method != Method::HEAD {
             return Err(Error::new(
                 405,
                 "MethodNotAllowed",
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response::JsonResponseBuilder,
+        uri::QueryMap
+    },
+    db::record::{AuditEntry, AuditQuery},
+    components::auth::{require_session, require_self_or_admin},
+    state::AppState
+};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+// users can only view their own actions. administrators can view everything
+// or filter to a single user with users_id
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let query_map = QueryMap::new(req.uri());
+    let start: Option<DateTime<Utc>> = query_map.get_parsed("start")?;
+    let end: Option<DateTime<Utc>> = query_map.get_parsed("end")?;
+    let users_id: Option<i64> = query_map.get_parsed("users_id")?;
+    let limit: i64 = query_map.get_parsed("limit")?.unwrap_or(DEFAULT_LIMIT);
+    let offset: i64 = query_map.get_parsed("offset")?.unwrap_or(0);
+
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(Error::new(400, "InvalidLimit", format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    if offset < 0 {
+        return Err(Error::new(400, "InvalidOffset", "offset cannot be negative"));
+    }
+
+    if let (Some(start), Some(end)) = (start.as_ref(), end.as_ref()) {
+        if start > end {
+            return Err(Error::new(400, "InvalidRange", "start cannot be after end"));
+        }
+    }
+
+    let users_id = if let Some(users_id) = users_id {
+        require_self_or_admin(&user, &users_id)?;
+
+        Some(users_id)
+    } else if user.is_admin() {
+        None
+    } else {
+        Some(user.id)
+    };
+
+    let query = AuditQuery {
+        start,
+        end,
+        users_id,
+        action: query_map.get_parsed("action")?,
+        target_id: query_map.get_parsed("target_id")?
+    };
+
+    let total = AuditEntry::count(&*conn, &query).await?;
+    let entries = AuditEntry::find(&*conn, &query, &limit, &offset).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+            "entries": entries
+        }))
+}
\ No newline at end of file
@@ -1,2 +1,3 @@
 pub mod session;
-pub mod password;
\ No newline at end of file
+pub mod password;
+pub mod verify;
\ No newline at end of file
@@ -15,7 +15,10 @@ use crate::{
     },
     security::argon::hash_with_default,
     state::AppState,
-    components::auth::{verify_totp_code, require_session}, 
+    components::{
+        auth::{verify_totp_code, require_session},
+        audit::{AuditContext, action}
+    }, 
     db::record::UserSession
 };
 
@@ -27,9 +30,10 @@ pub struct PasswordJson {
 }
 
 pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
-    let (head, body) = req.into_parts();
+    let (mut head, body) = req.into_parts();
     let mut conn = state.db.pool.get().await?;
-    let (user, _) = require_session(&*conn, &head.headers).await?;
+    let (user, _) = require_session(&mut head.extensions)?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
 
     let json: PasswordJson = json_from_body(body).await?;
 
@@ -47,7 +51,7 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
 
     let new_hash = hash_with_default(&json.new_password)?;
     let session_duration = UserSession::default_duration();
-    let session_record = UserSession::new(user.id.clone(), &session_duration)?;
+    let session_record = UserSession::new(user.id, &session_duration)?;
     let transaction = conn.transaction().await?;
 
     try_join(
@@ -61,6 +65,12 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
         )
     ).await?;
 
+    // all previous sessions are dropped so the new session replaces the one
+    // used to make the request
+    session_record.insert(&transaction).await?;
+
+    audit.entry(Some(user.id), action::PASSWORD_CHANGED).insert(&transaction).await?;
+
     transaction.commit().await?;
 
     let mut session_cookie = SetCookie::new("session_id", session_record.token.to_string());
@@ -1,6 +1,7 @@
 use argon2::verify_encoded;
 use hyper::{Body, header::SET_COOKIE};
 use serde::Deserialize;
+use serde_json::json;
 use tokio_postgres::GenericClient;
 
 use crate::{
@@ -19,7 +20,8 @@ use crate::{
             response_index_html_parts,
             check_if_html_headers
         }, 
-        auth::{verify_totp_code, require_session}
+        auth::{verify_totp_code, get_session},
+        audit::{AuditContext, action}
     }, 
     state::AppState
 };
@@ -31,26 +33,43 @@ struct LoginJson {
     totp: Option<String>
 }
 
-async fn create_session(conn: &impl GenericClient, body: Body,) -> Result<Response> {
+// failed logins are recorded on a separate connection so that they are kept
+// if the login was attempted inside of a transaction
+async fn record_failed_login(state: &AppState, audit: &AuditContext, users_id: Option<i64>, username: &String) -> Result<()> {
+    let conn = state.db.pool.get().await?;
+    let mut entry = audit.entry(users_id, action::LOGIN_FAILED);
+    entry.details = json!({"username": username});
+    entry.insert(&*conn).await
+}
+
+async fn create_session(state: &AppState, conn: &impl GenericClient, audit: &AuditContext, body: Body,) -> Result<Response> {
     let login_json: LoginJson = json_from_body(body).await?;
 
     if let Some(user) = User::find_username(conn, &login_json.username).await? {
         if !verify_encoded(&user.hash, login_json.password.as_bytes())? {
+            record_failed_login(state, audit, Some(user.id), &login_json.username).await?;
+
             return Err(Error::new(401, "InvalidLogin", "invalid password given"));
         }
 
         if user.totp_enabled {
             if let Some(code) = login_json.totp {
-                verify_totp_code(&user, code)?;
+                if let Err(err) = verify_totp_code(&user, code) {
+                    record_failed_login(state, audit, Some(user.id), &login_json.username).await?;
+
+                    return Err(err);
+                }
             } else {
                 return Err(Error::new(400, "MissingTOTP", "requires totp code"));
             }
         }
 
         let session_duration = UserSession::default_duration();
-        let session_record = UserSession::new(user.id.clone(), &session_duration)?;
+        let session_record = UserSession::new(user.id, &session_duration)?;
         session_record.insert(conn).await?;
 
+        audit.entry(Some(user.id), action::LOGIN).insert(conn).await?;
+
         let mut session_cookie = SetCookie::new("session_id", session_record.token.to_string());
         session_cookie.path = Some("/".into());
         session_cookie.max_age = Some(session_duration);
@@ -61,6 +80,8 @@ async fn create_session(conn: &impl GenericClient, body: Body,) -> Result<Respon
             .add_header(SET_COOKIE, session_cookie)
             .response()
     } else {
+        record_failed_login(state, audit, None, &login_json.username).await?;
+
         Err(Error::new(404, "UsernameNotFound", "requested username was not found"))
     }
 }
@@ -68,15 +89,17 @@ async fn create_session(conn: &impl GenericClient, body: Body,) -> Result<Respon
 pub async fn handle_get(state: AppState, req: Request) -> Result<Response> {
     let (head, _) = req.into_parts();
     let conn = state.db.pool.get().await?;
-    let session_check = require_session(&*conn, &head.headers).await;
+    let session_check = get_session(&*conn, &head.headers).await;
 
     if check_if_html_headers(&head.headers)? {
         match session_check {
-            Ok(_) => redirect_response("/auth/session"),
-            Err(_) => response_index_html_parts(state.template)
+            Ok(Some(_)) => redirect_response("/auth/session"),
+            _ => response_index_html_parts(state.template)
         }
     } else {
-        session_check?;
+        if session_check?.is_none() {
+            return Err(Error::new(401, "NoSession", "no session is available"));
+        }
 
         JsonResponseBuilder::new(200)
             .set_message("noop")
@@ -87,6 +110,7 @@ pub async fn handle_get(state: AppState, req: Request) -> Result<Response> {
 pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
     let (head, body) = req.into_parts();
     let mut conn = state.db.pool.get().await?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
 
     if let Some(_auth) = head.headers.get("authorization") {
         // do something
@@ -104,7 +128,7 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
                         &[session_id]
                     ).await?;
                     
-                    let res = create_session(&transaction, body).await?;
+                    let res = create_session(&state, &transaction, &audit, body).await?;
 
                     transaction.commit().await?;
 
@@ -116,12 +140,13 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
         }
     }
 
-    create_session(&*conn, body).await
+    create_session(&state, &*conn, &audit, body).await
 }
 
 pub async fn handle_delete(state: AppState, req: Request) -> Result<Response> {
     let (head, _) = req.into_parts();
     let conn = state.db.pool.get().await?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
 
     if let Some(_auth) = head.headers.get("authorization") {
         // do something
@@ -132,11 +157,13 @@ pub async fn handle_delete(state: AppState, req: Request) -> Result<Response> {
 
         if let Some(list) = cookies.get(&session_id_key) {
             if let Ok(session_id) = &list[0].parse::<uuid::Uuid>() {
-                if let Some(_session) = UserSession::find_token(&*conn, session_id).await? {
+                if let Some(session) = UserSession::find_token(&*conn, session_id).await? {
                     conn.execute(
                         "update user_sessions set dropped = true where token = $1",
                         &[session_id]
                     ).await?;
+
+                    audit.entry(Some(session.users_id), action::LOGOUT).insert(&*conn).await?;
                 }
             } else {
                 return Err(Error::new(400, "InvalidSessionId", "given session id cannot be parsed"))
@@ -0,0 +1,50 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response::JsonResponseBuilder,
+        uri::QueryMap
+    },
+    state::AppState
+};
+
+pub async fn handle_get(state: AppState, req: Request) -> Result<Response> {
+    let mut conn = state.db.pool.get().await?;
+    let query_map = QueryMap::new(req.uri());
+    let key_id: String = query_map.get_parsed("key")?
+        .ok_or(Error::new(400, "MissingKey", "no verification key was given"))?;
+
+    let transaction = conn.transaction().await?;
+    let record = transaction.query_opt(
+        "\
+        delete from email_verificiations \
+        where key_id = $1 \
+        returning users_id, issued",
+        &[&key_id]
+    ).await?
+        .ok_or(Error::new(404, "KeyNotFound", "the given verification key was not found"))?;
+
+    let users_id: i64 = record.get(0);
+    let issued: chrono::DateTime<Utc> = record.get(1);
+
+    if issued + Duration::days(1) < Utc::now() {
+        // the expired key is still removed
+        transaction.commit().await?;
+
+        return Err(Error::new(400, "KeyExpired", "the given verification key has expired"));
+    }
+
+    transaction.execute(
+        "update users set email_verified = true where id = $1",
+        &[&users_id]
+    ).await?;
+
+    transaction.commit().await?;
+
+    JsonResponseBuilder::new(200)
+        .set_message("email verified")
+        .response()
+}
\ No newline at end of file
@@ -0,0 +1,95 @@
+use serde_json::json;
+
+use crate::{
+    state::AppState,
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response::JsonResponseBuilder,
+        uri
+    },
+    components::{
+        auth::{require_session, require_admin},
+        fs_items::{existing_resource, SearchOptions}
+    },
+    db::record::{FsItemChange, join_path},
+    routing::Params
+};
+
+const DEFAULT_LIMIT: i64 = 100;
+const MAX_LIMIT: i64 = 1000;
+
+// returns changes for an item and everything under it after the given
+// cursor. clients keep the returned cursor and pass it back on the next call
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let mut conn = state.db.pool.get().await?;
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+
+    let (user, _) = require_session(req.extensions_mut())?;
+    let query_map = uri::QueryMap::new(req.uri());
+    let context = params.get_value_ref("context").unwrap();
+    let cursor: i64 = query_map.get_parsed("cursor")?.unwrap_or(0);
+    let limit: i64 = query_map.get_parsed("limit")?.unwrap_or(DEFAULT_LIMIT);
+    let mut search_options = SearchOptions::new(user.id);
+    search_options.pull_from_query_map(&query_map)?;
+
+    if cursor < 0 {
+        return Err(Error::new(400, "InvalidCursor", "cursor cannot be negative"));
+    }
+
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(Error::new(400, "InvalidLimit", format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    if let Some(fs_item) = existing_resource(&*conn, context, search_options).await? {
+        if fs_item.users_id != user.id {
+            require_admin(&user)?;
+        }
+
+        {
+            let transaction = conn.transaction().await?;
+            FsItemChange::assign_pending(&transaction).await?;
+            transaction.commit().await?;
+        }
+
+        // everything a user has is under their root so there is no need to
+        // filter by path
+        let path = if fs_item.is_root {
+            None
+        } else {
+            Some(join_path(&fs_item.directory, &fs_item.basename))
+        };
+
+        // one extra is requested to know if there is another page
+        let mut changes = FsItemChange::find_after(
+            &*conn,
+            &fs_item.users_id,
+            &fs_item.id,
+            path.as_deref(),
+            &cursor,
+            &(limit + 1)
+        ).await?;
+        let has_more = changes.len() as i64 > limit;
+
+        if has_more {
+            changes.truncate(limit as usize);
+        }
+
+        let next_cursor = changes.last().map(|change| change.seq).unwrap_or(cursor);
+
+        // a client starting fresh can record latest before listing the tree
+        // and use it as its first cursor
+        let latest = FsItemChange::latest_seq(&*conn, &fs_item.users_id).await?;
+
+        JsonResponseBuilder::new(200)
+            .payload_response(json!({
+                "cursor": next_cursor,
+                "latest": latest,
+                "has_more": has_more,
+                "changes": changes
+            }))
+    } else {
+        Err(Error::new(404, "PathNotFound", "requested path was not found"))
+    }
+}
\ No newline at end of file
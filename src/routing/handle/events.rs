@@ -0,0 +1,215 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use hyper::{Body, upgrade::Upgraded};
+use hyper::header::{CONNECTION, UPGRADE, SEC_WEBSOCKET_KEY, SEC_WEBSOCKET_ACCEPT, CACHE_CONTROL, CONTENT_TYPE};
+use serde_json::json;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use tokio::time::{interval, Interval};
+use tokio_tungstenite::WebSocketStream;
+use ring::digest;
+use tokio_tungstenite::tungstenite::{self, Message, protocol::Role};
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response,
+        uri::QueryMap
+    },
+    db::record::{FsItem, FsItemType},
+    components::{
+        auth::{require_session, require_admin},
+        fs_items::{existing_resource, SearchOptions}
+    },
+    event::BusEvent,
+    shutdown::ArcShutdownState,
+    routing::Params,
+    state::AppState
+};
+
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+// finds the directory that the client wants to subscribe to. events for the
+// directory and anything below it will be sent
+async fn find_subscription(state: &AppState, req: &mut Request) -> Result<FsItem> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let query_map = QueryMap::new(req.uri());
+    let context = params.get_value_ref("context").unwrap();
+    let mut search_options = SearchOptions::new(user.id);
+    search_options.pull_from_query_map(&query_map)?;
+
+    if let Some(fs_item) = existing_resource(&*conn, context, search_options).await? {
+        if fs_item.users_id != user.id {
+            require_admin(&user)?;
+        }
+
+        if fs_item.item_type != FsItemType::Dir {
+            return Err(Error::new(400, "NotADirectory", "events can only be subscribed to for directories"));
+        }
+
+        Ok(fs_item)
+    } else {
+        Err(Error::new(404, "PathNotFound", "requested path was not found"))
+    }
+}
+
+struct SseStream {
+    receiver: Receiver<Arc<BusEvent>>,
+    id: i64,
+    keep_alive: Interval,
+    shutdown: ArcShutdownState
+}
+
+async fn next_sse_chunk(mut stream: SseStream) -> Option<(std::io::Result<String>, SseStream)> {
+    loop {
+        tokio::select! {
+            result = stream.receiver.recv() => {
+                match result {
+                    Ok(event) => {
+                        if event.is_within(&stream.id) {
+                            let chunk = format!("event: {}\ndata: {}\n\n", event.name, event.payload);
+
+                            return Some((Ok(chunk), stream));
+                        }
+                    },
+                    Err(RecvError::Lagged(count)) => {
+                        let chunk = format!("event: lagged\ndata: {}\n\n", json!({"missed": count}));
+
+                        return Some((Ok(chunk), stream));
+                    },
+                    Err(RecvError::Closed) => {
+                        return None;
+                    }
+                }
+            },
+            _ = stream.keep_alive.tick() => {
+                return Some((Ok(": keep-alive\n\n".to_owned()), stream));
+            },
+            // ending the stream lets the connection close so the server can
+            // finish shutting down
+            _ = stream.shutdown.wait() => {
+                return None;
+            }
+        }
+    }
+}
+
+pub async fn handle_get_sse(state: AppState, mut req: Request) -> Result<Response> {
+    let fs_item = find_subscription(&state, &mut req).await?;
+    let stream = SseStream {
+        receiver: state.events.subscribe(),
+        id: fs_item.id,
+        keep_alive: interval(KEEP_ALIVE),
+        shutdown: state.shutdown.clone()
+    };
+
+    Ok(response::build()
+        .status(200)
+        .header(CONTENT_TYPE, "text/event-stream")
+        .header(CACHE_CONTROL, "no-cache")
+        .body(Body::wrap_stream(futures::stream::unfold(stream, next_sse_chunk)))?)
+}
+
+async fn ws_session(mut ws: WebSocketStream<Upgraded>, mut receiver: Receiver<Arc<BusEvent>>, id: i64, shutdown: ArcShutdownState) -> std::result::Result<(), tungstenite::Error> {
+    loop {
+        tokio::select! {
+            result = receiver.recv() => {
+                match result {
+                    Ok(event) => {
+                        if event.is_within(&id) {
+                            ws.send(Message::Text(event.payload.clone())).await?;
+                        }
+                    },
+                    Err(RecvError::Lagged(count)) => {
+                        ws.send(Message::Text(json!({"event": "lagged", "missed": count}).to_string())).await?;
+                    },
+                    Err(RecvError::Closed) => {
+                        break;
+                    }
+                }
+            },
+            msg = ws.next() => {
+                match msg {
+                    Some(Ok(Message::Close(_))) | None => {
+                        break;
+                    },
+                    // pings are answered by tungstenite and anything else
+                    // from the client is ignored
+                    Some(Ok(_)) => {},
+                    Some(Err(err)) => {
+                        return Err(err);
+                    }
+                }
+            },
+            _ = shutdown.wait() => {
+                ws.close(None).await?;
+
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// value for sec-websocket-accept from RFC 6455 section 4.2.2
+fn derive_accept_key(key: &[u8]) -> String {
+    const WS_GUID: &[u8] = b"258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+    let mut context = digest::Context::new(&digest::SHA1_FOR_LEGACY_USE_ONLY);
+    context.update(key);
+    context.update(WS_GUID);
+
+    base64::encode(context.finish())
+}
+
+pub async fn handle_get_ws(state: AppState, mut req: Request) -> Result<Response> {
+    let fs_item = find_subscription(&state, &mut req).await?;
+
+    let is_upgrade = req.headers().get(UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false);
+
+    if !is_upgrade {
+        return Err(Error::new(400, "ExpectedWebSocket", "request must be a websocket upgrade"));
+    }
+
+    let accept = if let Some(key) = req.headers().get(SEC_WEBSOCKET_KEY) {
+        derive_accept_key(key.as_bytes())
+    } else {
+        return Err(Error::new(400, "MissingWebSocketKey", "no websocket key was given"));
+    };
+
+    let on_upgrade = hyper::upgrade::on(&mut req);
+    let receiver = state.events.subscribe();
+    let id = fs_item.id;
+    let shutdown = state.shutdown.clone();
+
+    state.offload.spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => {
+                let ws = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+
+                if let Err(err) = ws_session(ws, receiver, id, shutdown).await {
+                    log::error!("websocket session error. {}", err);
+                }
+            },
+            Err(err) => {
+                log::error!("failed to upgrade connection. {}", err);
+            }
+        }
+    });
+
+    Ok(response::build()
+        .status(101)
+        .header(CONNECTION, "upgrade")
+        .header(UPGRADE, "websocket")
+        .header(SEC_WEBSOCKET_ACCEPT, accept)
+        .body(Body::empty())?)
+}
\ No newline at end of file
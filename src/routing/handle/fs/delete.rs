@@ -2,9 +2,11 @@ use std::collections::HashSet;
 use std::io::ErrorKind;
 
 use futures::{pin_mut, TryStreamExt};
+use serde_json::json;
 use tokio::fs::{remove_file, remove_dir};
 
-use crate::components::auth::require_session;
+use crate::components::auth::{require_session, require_admin};
+use crate::components::audit::{AuditContext, action};
 use crate::components::fs_items::{existing_resource, SearchOptions};
 use crate::db::record::FsItemType;
 use crate::event;
@@ -18,7 +20,8 @@ pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response
     let params = req.extensions_mut().remove::<Params>().unwrap();
     let mut conn = state.db.pool.get().await?;
 
-    let (user, _) = require_session(&*conn, req.headers()).await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
     let query_map = uri::QueryMap::new(req.uri());
     let context = params.get_value_ref("context").unwrap();
     let mut search_options = SearchOptions::new(user.id);
@@ -30,17 +33,26 @@ pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response
         }
 
         if fs_item.users_id != user.id {
-            // permissions check
+            require_admin(&user)?;
         }
 
         let mut fs_path = state.storage.directory.clone();
         fs_path.push(&fs_item.directory);
         fs_path.push(&fs_item.basename);
 
-        let mut deleted_records: Vec<i64> = Vec::new();
+        let _write = state.storage.writes.track_tree(fs_path.clone());
+        let enqueued: (usize, event::BusEvent);
+        let deleted_ids: Vec<i64>;
         let transaction = conn.transaction().await?;
 
         if fs_item.item_type == FsItemType::File {
+            enqueued = event::enqueue_fs_item_deleted(&transaction, &fs_item, &vec![fs_item.id]).await?;
+
+            transaction.execute(
+                "delete from event_listeners where ref_table = 'fs_items' and ref_id = $1",
+                &[&fs_item.id]
+            ).await?;
+
             transaction.execute(
                 "delete from fs_items where id = $1",
                 &[&fs_item.id]
@@ -58,7 +70,7 @@ pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response
                 }
             };
 
-            deleted_records.push(fs_item.id);
+            deleted_ids = vec![fs_item.id];
         } else {
             let row_stream = transaction.query_raw(
                 "\
@@ -160,20 +172,47 @@ pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response
                 }
             }
 
+            enqueued = event::enqueue_fs_item_deleted(&transaction, &fs_item, &marked_delete).await?;
+
+            transaction.execute(
+                "delete from event_listeners where ref_table = 'fs_items' and ref_id = any($1)",
+                &[&marked_delete]
+            ).await?;
+
             transaction.execute(
                 "delete from fs_items where id = any(($1))",
                 &[&marked_delete]
             ).await?;
 
-            deleted_records = marked_delete;
+            deleted_ids = marked_delete;
+        }
+
+        {
+            // the first target is always the requested item
+            let mut targets = Vec::with_capacity(deleted_ids.len() + 1);
+            targets.push(fs_item.id);
+            targets.extend(deleted_ids.iter().filter(|id| **id != fs_item.id));
+
+            let mut entry = audit.entry(Some(user.id), action::FS_ITEM_DELETED);
+            entry.set_target("fs_items", targets);
+            entry.details = json!({
+                "directory": &fs_item.directory,
+                "basename": &fs_item.basename,
+                "item_type": &fs_item.item_type,
+                "owner": fs_item.users_id
+            });
+            entry.insert(&transaction).await?;
         }
 
         transaction.commit().await?;
 
-        state.offload.spawn(event::trigger_fs_item_deleted(
-            &state,
-            deleted_records
-        ));
+        let (delivery_count, bus_event) = enqueued;
+
+        state.events.publish(bus_event);
+
+        if delivery_count > 0 {
+            state.events.notify();
+        }
 
         JsonResponseBuilder::new(204)
             .response()
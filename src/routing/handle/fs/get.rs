@@ -1,11 +1,13 @@
+use std::collections::HashMap;
+
+use serde_json::Value as JsonValue;
 use tokio::fs::File as TokioFile;
 use tokio_util::codec::{FramedRead, BytesCodec};
 use hyper::Body;
 
-use crate::components::auth::{require_session, login_redirect};
+use crate::components::auth::{require_session, require_admin};
 use crate::components::fs_items::{existing_resource, SearchOptions};
-use crate::components::html::{check_if_html_headers, response_index_html_parts};
-use crate::db::record::{FsItem, FsItemType, User};
+use crate::db::record::{FsItem, FsItemType, User, DirStats};
 use crate::db::types::PoolConn;
 use crate::http::response::JsonResponseBuilder;
 use crate::http::uri;
@@ -15,11 +17,27 @@ use crate::http::{mime, response};
 use crate::routing::Params;
 use crate::state::AppState;
 
+// directories do not track their own size so the recursive totals are
+// added to the json of the item
+fn fs_item_json(fs_item: FsItem, dir_stats: &HashMap<i64, DirStats>) -> Result<JsonValue> {
+    let stats = dir_stats.get(&fs_item.id);
+    let mut json = serde_json::to_value(fs_item)?;
+
+    if let Some(stats) = stats {
+        let object = json.as_object_mut().unwrap();
+        object.insert("item_size".into(), stats.item_size.into());
+        object.insert("file_count".into(), stats.file_count.into());
+        object.insert("dir_count".into(), stats.dir_count.into());
+    }
+
+    Ok(json)
+}
+
 async fn handle_get_info(
     _state: &AppState, 
     conn: &PoolConn<'_>, 
     _query_map: uri::QueryMap, 
-    user: User, 
+    _user: User, 
     fs_item: FsItem
 ) -> Result<Response> {
     match fs_item.item_type {
@@ -31,13 +49,28 @@ async fn handle_get_info(
         FsItemType::Dir => {
             let dir_items = FsItem::find_dir_contents(
                 &**conn,
-                &user.id,
+                &fs_item.users_id,
                 &Some(fs_item.id)
             ).await?;
-            let mut fs_item_json = serde_json::to_value(fs_item)?;
+            let mut dir_ids = vec![fs_item.id];
+
+            for item in dir_items.iter() {
+                if item.item_type == FsItemType::Dir {
+                    dir_ids.push(item.id);
+                }
+            }
+
+            let dir_stats = DirStats::find_ids(&**conn, &dir_ids).await?;
+            let mut contents = Vec::with_capacity(dir_items.len());
+
+            for item in dir_items {
+                contents.push(fs_item_json(item, &dir_stats)?);
+            }
+
+            let mut fs_item_json = fs_item_json(fs_item, &dir_stats)?;
             fs_item_json.as_object_mut().unwrap().insert(
                 "contents".into(),
-                serde_json::to_value(dir_items)?
+                JsonValue::Array(contents)
             );
 
             JsonResponseBuilder::new(200)
@@ -103,22 +136,17 @@ async fn handle_get_download(
 pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
     let params = req.extensions_mut().remove::<Params>().unwrap();
     let conn = state.db.pool.get().await?;
-    let session_tuple = require_session(&*conn, req.headers()).await;
-
-    if check_if_html_headers(req.headers())? {
-        return match session_tuple {
-            Ok(_) => response_index_html_parts(state.template),
-            Err(_) => login_redirect(req.uri())
-        }
-    }
-
-    let (user, _) = session_tuple?;
+    let (user, _) = require_session(req.extensions_mut())?;
     let query_map = uri::QueryMap::new(req.uri());
     let context = params.get_value_ref("context").unwrap();
     let mut search_options = SearchOptions::new(user.id);
     search_options.pull_from_query_map(&query_map)?;
 
     if let Some(fs_item) = existing_resource(&*conn, context, search_options).await? {
+        if fs_item.users_id != user.id {
+            require_admin(&user)?;
+        }
+
         let mut action = "info";
 
         if let Some(value) = query_map.get_value_ref("action") {
@@ -2,11 +2,12 @@ use chrono::Utc;
 use serde_json::json;
 use tokio::fs::create_dir;
 
-use crate::components::auth::require_session;
+use crate::components::auth::{require_session, require_admin};
+use crate::components::audit::{AuditContext, action};
 use crate::components::fs_items::{new_resource, SearchOptions};
-use crate::db::record::{FsItem, FsItemType};
+use crate::components::quota::{get_quota, check_upload, write_upload};
+use crate::db::record::{FsItem, FsItemType, User};
 use crate::event;
-use crate::http::body::file_from_body;
 use crate::http::response::JsonResponseBuilder;
 use crate::http::uri::QueryMap;
 use crate::http::{Response, Request};
@@ -19,7 +20,8 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
     let params = head.extensions.remove::<Params>().unwrap();
     let mut conn = state.db.pool.get().await?;
 
-    let (user, _) = require_session(&*conn, &head.headers).await?;
+    let (user, _) = require_session(&mut head.extensions)?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
     let query_map = QueryMap::new(&head.uri);
     let context = params.get_value_ref("context").unwrap();
     let mut search_options = SearchOptions::new(user.id);
@@ -28,9 +30,18 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
     let (parent, basename) = new_resource(&*conn, context, search_options).await?;
 
     if let Some(fs_parent) = parent {
-        if fs_parent.users_id != user.id {
-            // check permissions
-        }
+        // the quota applied is for the owner of the parent not the user
+        // making the request
+        let quota = if fs_parent.users_id != user.id {
+            require_admin(&user)?;
+
+            let owner = User::find_id(&*conn, &fs_parent.users_id).await?
+                .ok_or(Error::new(500, "MissingOwner", "the owner of the requested path was not found"))?;
+
+            get_quota(&state, &*conn, &owner).await?
+        } else {
+            get_quota(&state, &*conn, &user).await?
+        };
 
         let post_type = if let Some(key_value) = query_map.get_value("type") {
             if let Some(existing) = key_value {
@@ -63,6 +74,7 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
             rtn.push(&basename);
             rtn
         };
+        let _write = state.storage.writes.track(post_path.clone());
 
         let fs_type = FsItemType::from(post_type.as_str());
 
@@ -93,7 +105,7 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
             record.modified = Some(Utc::now());
 
             transaction.execute(
-                "update fs_items set item_exists = true, modified = $2 where id = $1",
+                "update fs_items set item_exists = true, modified = $2, checksum = null where id = $1",
                 &[&record.id, &record.modified]
             ).await?;
 
@@ -118,7 +130,7 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
                 id: state.snowflakes.fs_items.next_id().await?,
                 item_type: fs_type,
                 parent: Some(fs_parent.id),
-                users_id: user.id,
+                users_id: fs_parent.users_id,
                 directory,
                 basename,
                 item_size: 0,
@@ -137,7 +149,8 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
 
         rtn_record.item_size = match &rtn_record.item_type {
             FsItemType::File => {
-                let (_, size) = file_from_body(&post_path, false, body).await?;
+                let limit = check_upload(&quota, rtn_record.item_size, &head.headers)?;
+                let size = write_upload(&state, &post_path, body, limit).await?;
 
                 size as i64
             },
@@ -155,15 +168,28 @@ pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
             ).await?;
         }
 
+        {
+            let mut entry = audit.entry(
+                Some(user.id),
+                if updated { action::FS_ITEM_UPDATED } else { action::FS_ITEM_CREATED }
+            );
+            entry.set_target("fs_items", [rtn_record.id]);
+            entry.details = json!({
+                "directory": &rtn_record.directory,
+                "basename": &rtn_record.basename
+            });
+            entry.insert(&transaction).await?;
+        }
+
         transaction.commit().await?;
 
         if updated {
-            state.offload.spawn(event::trigger_fs_item_updated(
+            state.spawn_tracked(event::trigger_fs_item_updated(
                 &state, 
                 rtn_record.clone()
             ));
         } else {
-            state.offload.spawn(event::trigger_fs_item_created(
+            state.spawn_tracked(event::trigger_fs_item_created(
                 &state, 
                 rtn_record.clone()
             ));
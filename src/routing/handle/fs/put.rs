@@ -1,21 +1,24 @@
 use chrono::Utc;
-use serde_json::Value as JsonValue;
-use hyper::Body;
+use serde_json::{json, Value as JsonValue};
+use hyper::{Body, HeaderMap};
 
-use crate::components::auth::require_session;
+use crate::components::auth::{require_session, require_admin};
+use crate::components::audit::{AuditContext, action};
 use crate::components::fs_items::{existing_resource, SearchOptions};
-use crate::db::record::{FsItem, FsItemType};
+use crate::components::quota::{get_quota, check_upload, write_upload};
+use crate::db::record::{FsItem, FsItemType, User, AuditEntry};
 use crate::db::types::PoolConn;
 use crate::event;
-use crate::http::body::{json_from_body, file_from_body};
+use crate::http::body::json_from_body;
 use crate::http::response::JsonResponseBuilder;
 use crate::http::{Response, Request};
 use crate::http::error::{Error, Result};
 use crate::http::uri;
 use crate::routing::Params;
+use crate::routing::layer::JsonBodyLimit;
 use crate::state::AppState;
 
-async fn handle_put_upload_action(state: &AppState, conn: PoolConn<'_>, mut fs_item: FsItem, body: Body) -> Result<Response> {
+async fn handle_put_upload_action(state: &AppState, conn: PoolConn<'_>, headers: &HeaderMap, mut audit: AuditEntry, mut fs_item: FsItem, body: Body) -> Result<Response> {
     if fs_item.is_root {
         return Err(Error::new(400, "CannotPutRoot", "you cannot update your root directory"));
     }
@@ -26,8 +29,14 @@ async fn handle_put_upload_action(state: &AppState, conn: PoolConn<'_>, mut fs_i
         path.push(&fs_item.basename);
         path
     };
+    let _write = state.storage.writes.track(file_path.clone());
 
-    let (_, size) = file_from_body(&file_path, true, body).await?;
+    let owner = User::find_id(&*conn, &fs_item.users_id).await?
+        .ok_or(Error::new(500, "MissingOwner", "the owner of the requested path was not found"))?;
+    let quota = get_quota(state, &*conn, &owner).await?;
+    let limit = check_upload(&quota, fs_item.item_size, headers)?;
+
+    let size = write_upload(state, &file_path, body, limit).await?;
 
     {
         let item_size = size as i64;
@@ -35,18 +44,26 @@ async fn handle_put_upload_action(state: &AppState, conn: PoolConn<'_>, mut fs_i
 
         conn.execute(
             "\
-            update fs_item \
+            update fs_items \
             set modified = $2, \
                 item_size = $3, \
+                checksum = null, \
                 item_exists = true \
-            where $1",
+            where id = $1",
             &[&fs_item.id, &modified, &item_size]
         ).await?;
 
+        fs_item.item_size = item_size;
         fs_item.modified = Some(modified);
     }
 
-    state.offload.spawn(event::trigger_fs_item_updated(
+    audit.details = json!({
+        "action": "upload",
+        "item_size": fs_item.item_size
+    });
+    audit.insert(&*conn).await?;
+
+    state.spawn_tracked(event::trigger_fs_item_updated(
         state,
         fs_item.clone()
     ));
@@ -55,8 +72,8 @@ async fn handle_put_upload_action(state: &AppState, conn: PoolConn<'_>, mut fs_i
         .payload_response(fs_item)
 }
 
-async fn handle_put_user_data_action(state: &AppState, conn: PoolConn<'_>, mut fs_item: FsItem, body: Body) -> Result<Response> {
-    let json: JsonValue = json_from_body(body).await?;
+async fn handle_put_user_data_action(state: &AppState, conn: PoolConn<'_>, json_limit: JsonBodyLimit, mut audit: AuditEntry, mut fs_item: FsItem, body: Body) -> Result<Response> {
+    let json: JsonValue = json_from_body(json_limit.apply(body)).await?;
 
     conn.execute(
         "update fs_items set user_data = $2 where id = $1",
@@ -65,7 +82,12 @@ async fn handle_put_user_data_action(state: &AppState, conn: PoolConn<'_>, mut f
 
     fs_item.user_data = json;
 
-    state.offload.spawn(event::trigger_fs_item_updated(
+    audit.details = json!({
+        "action": "user_data"
+    });
+    audit.insert(&*conn).await?;
+
+    state.spawn_tracked(event::trigger_fs_item_updated(
         state, 
         fs_item.clone()
     ));
@@ -79,7 +101,7 @@ pub async fn handle_put(state: AppState, req: Request) -> Result<Response> {
     let params = head.extensions.remove::<Params>().unwrap();
     let conn = state.db.pool.get().await?;
 
-    let (user, _) = require_session(&*conn, &head.headers).await?;
+    let (user, _) = require_session(&mut head.extensions)?;
     let query_map = uri::QueryMap::new(&head.uri);
     let context = params.get_value_ref("context").unwrap();
     let mut search_options = SearchOptions::new(user.id);
@@ -87,29 +109,33 @@ pub async fn handle_put(state: AppState, req: Request) -> Result<Response> {
 
     if let Some(fs_item) = existing_resource(&*conn, context, search_options).await? {
         if fs_item.users_id != user.id {
-            // permissions check
+            require_admin(&user)?;
         }
 
+        let mut audit = AuditContext::new(&head.headers, &head.extensions)
+            .entry(Some(user.id), action::FS_ITEM_UPDATED);
+        audit.set_target("fs_items", [fs_item.id]);
+
         let query_map = uri::QueryMap::new(&head.uri);
-        let action = if let Some(action) = query_map.get_value("action") {
-            if let Some(action_value) = action {
-                action_value
-            } else {
-                "upload".into()
-            }
-        } else {
-            "upload".into()
-        };
+        let action = query_map.get_value("action")
+            .flatten()
+            .unwrap_or("upload".into());
 
         match action.as_str() {
             "upload" => {
                 if fs_item.item_type == FsItemType::Dir {
                     Err(Error::new(400, "InvalidAction", "cannot upload a file as a directory"))
                 } else {
-                    handle_put_upload_action(&state, conn, fs_item, body).await
+                    handle_put_upload_action(&state, conn, &head.headers, audit, fs_item, body).await
                 }
             },
-            "user_data" => handle_put_user_data_action(&state, conn, fs_item, body).await,
+            "user_data" => {
+                // the route is given the upload limits so the json ones are
+                // applied here
+                let json_limit = head.extensions.remove::<JsonBodyLimit>().unwrap();
+
+                handle_put_user_data_action(&state, conn, json_limit, audit, fs_item, body).await
+            },
             _ => Err(Error::new(400, "UnknownAction", format!("requested action is unknown: \"{}\"", action)))
         }
     } else {
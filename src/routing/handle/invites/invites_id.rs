@@ -0,0 +1,29 @@
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response::JsonResponseBuilder
+    },
+    routing::Params,
+    state::AppState
+};
+
+pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let conn = state.db.pool.get().await?;
+
+    let invite_id = params.get_value_ref("invite_id").unwrap();
+
+    let result = conn.execute(
+        "delete from user_invites where id = $1",
+        &[&invite_id]
+    ).await?;
+
+    if result == 0 {
+        Err(Error::new(404, "InviteNotFound", "requested invite was not found"))
+    } else {
+        JsonResponseBuilder::new(200)
+            .response()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,77 @@
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        body::json_from_body,
+        response::JsonResponseBuilder
+    },
+    db::record::{UserInvite, UserRole},
+    components::auth::require_session,
+    security::rand::rand_hex,
+    state::AppState
+};
+
+pub mod invites_id;
+
+const DEFAULT_EXPIRES_HOURS: i64 = 24 * 7;
+
+pub async fn handle_get(state: AppState, _req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(UserInvite::find_all(&*conn).await?)
+}
+
+#[derive(Deserialize)]
+struct NewInviteJson {
+    email: Option<String>,
+    role: Option<UserRole>,
+    // number of hours until the invite expires
+    expires: Option<i64>
+}
+
+pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+    let (mut head, body) = req.into_parts();
+    let (user, _) = require_session(&mut head.extensions)?;
+
+    let json: NewInviteJson = json_from_body(body).await?;
+    let expires_hours = json.expires.unwrap_or(DEFAULT_EXPIRES_HOURS);
+
+    if expires_hours < 1 {
+        return Err(Error::new(400, "InvalidExpires", "expires must be at least 1 hour"));
+    }
+
+    let email = if let Some(email) = json.email {
+        let email = email.trim().to_owned();
+
+        if email.is_empty() {
+            None
+        } else {
+            Some(email)
+        }
+    } else {
+        None
+    };
+
+    let issued = Utc::now();
+    let invite = UserInvite {
+        id: rand_hex(16).ok_or(Error::from("failed to get random bytes from system"))?,
+        issued_by: user.id,
+        email,
+        role: json.role.unwrap_or(UserRole::User),
+        issued,
+        expires: issued + Duration::hours(expires_hours),
+        used_by: None,
+        used_on: None
+    };
+
+    invite.insert(&*conn).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(invite)
+}
\ No newline at end of file
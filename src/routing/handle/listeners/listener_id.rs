@@ -0,0 +1,288 @@
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        body::{json_from_body, deserialize_some},
+        response::JsonResponseBuilder,
+        uri::QueryMap
+    },
+    db::record::{EventListener, EventDelivery, DeliveryStatus, User, FsItemType},
+    components::{
+        auth::{require_session, require_self_or_admin},
+        audit::{AuditContext, action}
+    },
+    routing::Params,
+    state::AppState
+};
+
+use super::{EventNames, check_endpoint, check_event_names, check_filter};
+
+const DEFAULT_LIMIT: i64 = 25;
+const MAX_LIMIT: i64 = 100;
+
+fn get_listener_id(params: &Params) -> Result<Uuid> {
+    if let Some(given) = params.get_value_ref("listener_id") {
+        if let Ok(parsed) = given.parse() {
+            Ok(parsed)
+        } else {
+            Err(Error::new(400, "InvalidListenerId", "given listener id is not a valid uuid"))
+        }
+    } else {
+        Err(Error::new(400, "MissingListenerId", "no listener id was given"))
+    }
+}
+
+async fn find_listener(conn: &impl tokio_postgres::GenericClient, user: &User, params: &Params) -> Result<EventListener> {
+    let listener_id = get_listener_id(params)?;
+    let listener = EventListener::find_id(conn, &listener_id).await?
+        .ok_or(Error::new(404, "ListenerNotFound", "requested listener was not found"))?;
+
+    require_self_or_admin(user, &listener.users_id)?;
+
+    Ok(listener)
+}
+
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let listener = find_listener(&*conn, &user, &params).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(listener)
+}
+
+// nullable filter fields can be set to null to remove them
+#[derive(Deserialize)]
+struct UpdateListenerJson {
+    event_name: Option<EventNames>,
+    endpoint: Option<String>,
+
+    recursive: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    basename_pattern: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    item_type: Option<Option<FsItemType>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    min_size: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    max_size: Option<Option<i64>>
+}
+
+pub async fn handle_put(state: AppState, req: Request) -> Result<Response> {
+    let (mut head, body) = req.into_parts();
+    let params = head.extensions.remove::<Params>().unwrap();
+    let mut conn = state.db.pool.get().await?;
+    let (user, _) = require_session(&mut head.extensions)?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
+    let mut listener = find_listener(&*conn, &user, &params).await?;
+
+    let json: UpdateListenerJson = json_from_body(body).await?;
+    let mut invalid_event_name = Vec::new();
+    let mut invalid_endpoint = Vec::new();
+    let mut invalid_filter = Vec::new();
+
+    if let Some(given) = json.event_name {
+        if let Some(event_name) = check_event_names(given, &mut invalid_event_name) {
+            listener.event_name = event_name;
+        }
+    }
+
+    if let Some(endpoint) = json.endpoint {
+        match check_endpoint(&endpoint) {
+            Ok(()) => {
+                listener.endpoint = endpoint;
+            },
+            Err(err) => {
+                invalid_endpoint.push(err);
+            }
+        }
+    }
+
+    if let Some(recursive) = json.recursive {
+        listener.filter.recursive = recursive;
+    }
+
+    if let Some(basename_pattern) = json.basename_pattern {
+        listener.filter.basename_pattern = basename_pattern;
+    }
+
+    if let Some(item_type) = json.item_type {
+        listener.filter.item_type = item_type;
+    }
+
+    if let Some(min_size) = json.min_size {
+        listener.filter.min_size = min_size;
+    }
+
+    if let Some(max_size) = json.max_size {
+        listener.filter.max_size = max_size;
+    }
+
+    check_filter(&listener.filter, &mut invalid_filter);
+
+    if !invalid_event_name.is_empty() || !invalid_endpoint.is_empty() || !invalid_filter.is_empty() {
+        return JsonResponseBuilder::new(400)
+            .set_message("the given listener update is invalid")
+            .payload_response(json!({
+                "invalid_event_name": invalid_event_name,
+                "invalid_endpoint": invalid_endpoint,
+                "invalid_filter": invalid_filter
+            }));
+    }
+
+    let transaction = conn.transaction().await?;
+
+    listener.update(&transaction).await?;
+
+    let mut entry = audit.entry(Some(user.id), action::LISTENER_UPDATED);
+    entry.set_target("event_listeners", [listener.id]);
+    entry.insert(&transaction).await?;
+
+    transaction.commit().await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(listener)
+}
+
+pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let mut conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
+    let listener = find_listener(&*conn, &user, &params).await?;
+    let transaction = conn.transaction().await?;
+
+    transaction.execute(
+        "delete from event_listeners where id = $1",
+        &[&listener.id]
+    ).await?;
+
+    let mut entry = audit.entry(Some(user.id), action::LISTENER_DELETED);
+    entry.set_target("event_listeners", [listener.id]);
+    entry.insert(&transaction).await?;
+
+    transaction.commit().await?;
+
+    JsonResponseBuilder::new(200)
+        .response()
+}
+
+pub async fn handle_get_deliveries(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let listener = find_listener(&*conn, &user, &params).await?;
+
+    let query_map = QueryMap::new(req.uri());
+    let limit: i64 = query_map.get_parsed("limit")?.unwrap_or(DEFAULT_LIMIT);
+    let offset: i64 = query_map.get_parsed("offset")?.unwrap_or(0);
+
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(Error::new(400, "InvalidLimit", format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    if offset < 0 {
+        return Err(Error::new(400, "InvalidOffset", "offset cannot be negative"));
+    }
+
+    let total = EventDelivery::count_listener_id(&*conn, &listener.id).await?;
+    let deliveries = EventDelivery::find_listener_id(&*conn, &listener.id, &limit, &offset).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+            "deliveries": deliveries
+        }))
+}
+
+// requeues deliveries for the listener. a single delivery can be specified
+// with delivery_id otherwise all dead deliveries are requeued
+pub async fn handle_post_redeliver(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let listener = find_listener(&*conn, &user, &params).await?;
+
+    let query_map = QueryMap::new(req.uri());
+    let delivery_id: Option<Uuid> = query_map.get_parsed("delivery_id")?;
+    let pending: i16 = DeliveryStatus::Pending.into();
+    let dead: i16 = DeliveryStatus::Dead.into();
+    let now = Utc::now();
+
+    let requeued = if let Some(delivery_id) = delivery_id {
+        let result = conn.execute(
+            "\
+            update event_deliveries \
+            set status = $3, \
+                attempts = 0, \
+                next_attempt = $4 \
+            where id = $1 and \
+                  listener_id = $2",
+            &[&delivery_id, &listener.id, &pending, &now]
+        ).await?;
+
+        if result == 0 {
+            return Err(Error::new(404, "DeliveryNotFound", "requested delivery was not found"));
+        }
+
+        result
+    } else {
+        conn.execute(
+            "\
+            update event_deliveries \
+            set status = $2, \
+                attempts = 0, \
+                next_attempt = $3 \
+            where listener_id = $1 and \
+                  status = $4",
+            &[&listener.id, &pending, &now, &dead]
+        ).await?
+    };
+
+    if requeued > 0 {
+        state.events.notify();
+    }
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "requeued": requeued
+        }))
+}
+
+// generates a new signing secret for the listener. deliveries that have not
+// been sent yet will use the new secret
+pub async fn handle_post_rotate(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let mut conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let listener = find_listener(&*conn, &user, &params).await?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
+    let secret = super::new_secret()?;
+    let transaction = conn.transaction().await?;
+
+    transaction.execute(
+        "update event_listeners set secret = $2 where id = $1",
+        &[&listener.id, &secret]
+    ).await?;
+
+    let mut entry = audit.entry(Some(user.id), action::LISTENER_ROTATED);
+    entry.set_target("event_listeners", [listener.id]);
+    entry.insert(&transaction).await?;
+
+    transaction.commit().await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "id": listener.id,
+            "secret": secret
+        }))
+}
\ No newline at end of file
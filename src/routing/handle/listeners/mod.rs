@@ -0,0 +1,367 @@
+use hyper::Uri;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Error, Result},
+        response::JsonResponseBuilder,
+        body::json_from_body,
+    }, 
+    state::AppState,
+    components::{
+        auth::require_session,
+        audit::{AuditContext, action}
+    }, 
+    db::record::{EventListener, ListenerFilter}, 
+    security::rand::rand_hex,
+    event
+};
+
+pub mod listener_id;
+
+pub fn new_secret() -> Result<String> {
+    rand_hex(32).ok_or(Error::from("failed to get random bytes from system"))
+}
+
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(EventListener::find_user_id(&*conn, &user.id).await?)
+}
+
+// a listener can be given a single event name or a list of them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EventNames {
+    Single(String),
+    List(Vec<String>)
+}
+
+impl EventNames {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            EventNames::Single(name) => vec![name],
+            EventNames::List(list) => list
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct NewEventListener {
+    event_name: EventNames,
+    endpoint: String,
+
+    ref_table: String,
+    ref_id: i64,
+
+    #[serde(flatten)]
+    filter: ListenerFilter
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidEndpoint {
+    endpoint: String,
+    reason: String
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidEventName {
+    event_name: String,
+    reason: String
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidRefTable {
+    ref_table: String,
+    reason: String
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidFilter {
+    field: &'static str,
+    reason: String
+}
+
+fn check_uri(endpoint: String, uri: Uri) -> std::result::Result<(), InvalidEndpoint> {
+    if let Some(scheme) = uri.scheme() {
+        match scheme.as_str() {
+            "http" | "https" => {},
+            _ => {
+                return Err(InvalidEndpoint {
+                    endpoint,
+                    reason: "invalid scheme, must be http or https".into()
+                });
+            }
+        }
+    } else {
+        return Err(InvalidEndpoint {
+            endpoint,
+            reason: "no scheme specified for the given endpoing, must be http or https".into()
+        });
+    }
+
+    Ok(())
+}
+
+fn check_endpoint(endpoint: &str) -> std::result::Result<(), InvalidEndpoint> {
+    if let Ok(uri) = endpoint.parse::<Uri>() {
+        check_uri(endpoint.to_owned(), uri)
+    } else {
+        Err(InvalidEndpoint {
+            endpoint: endpoint.to_owned(),
+            reason: "failed to parse as a valid uri".into()
+        })
+    }
+}
+
+fn check_filter(filter: &ListenerFilter, invalid: &mut Vec<InvalidFilter>) -> bool {
+    let mut failed_check = false;
+
+    if let Some(pattern) = filter.basename_pattern.as_ref() {
+        if pattern.is_empty() {
+            invalid.push(InvalidFilter {
+                field: "basename_pattern",
+                reason: "pattern cannot be empty".into()
+            });
+            failed_check = true;
+        } else if let Err(err) = lib::glob::Pattern::new(pattern) {
+            invalid.push(InvalidFilter {
+                field: "basename_pattern",
+                reason: err.to_string()
+            });
+            failed_check = true;
+        }
+    }
+
+    if let Some(min) = filter.min_size {
+        if min < 0 {
+            invalid.push(InvalidFilter {
+                field: "min_size",
+                reason: "min size cannot be negative".into()
+            });
+            failed_check = true;
+        }
+    }
+
+    if let Some(max) = filter.max_size {
+        if max < 0 {
+            invalid.push(InvalidFilter {
+                field: "max_size",
+                reason: "max size cannot be negative".into()
+            });
+            failed_check = true;
+        } else if let Some(min) = filter.min_size {
+            if min > max {
+                invalid.push(InvalidFilter {
+                    field: "max_size",
+                    reason: "max size cannot be less than min size".into()
+                });
+                failed_check = true;
+            }
+        }
+    }
+
+    !failed_check
+}
+
+// removes any duplicate names and reports the names that are not known
+fn check_event_names(given: EventNames, invalid: &mut Vec<InvalidEventName>) -> Option<Vec<String>> {
+    let mut rtn: Vec<String> = Vec::new();
+    let mut failed_check = false;
+
+    for name in given.into_vec() {
+        match name.as_str() {
+            event::name::FS_ITEM_CREATED |
+            event::name::FS_ITEM_DELETED |
+            event::name::FS_ITEM_SYNCED |
+            event::name::FS_ITEM_UPDATED => {
+                if !rtn.contains(&name) {
+                    rtn.push(name);
+                }
+            },
+            _ => {
+                invalid.push(InvalidEventName {
+                    event_name: name,
+                    reason: "unknown event name given".into()
+                });
+                failed_check = true;
+            }
+        }
+    }
+
+    if rtn.is_empty() && !failed_check {
+        invalid.push(InvalidEventName {
+            event_name: String::new(),
+            reason: "at least one event name must be given".into()
+        });
+        failed_check = true;
+    }
+
+    if failed_check {
+        None
+    } else {
+        Some(rtn)
+    }
+}
+
+pub async fn handle_post(state: AppState, mut req: Request) -> Result<Response> {
+    let mut conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
+    let body = req.into_body();
+
+    let new_listeners: Vec<NewEventListener> = json_from_body(body).await?;
+    let mut invalid_event_name: Vec<InvalidEventName> = Vec::with_capacity(new_listeners.len());
+    let mut invalid_endpoint: Vec<InvalidEndpoint> = Vec::with_capacity(new_listeners.len());
+    let mut invalid_ref_table: Vec<InvalidRefTable> = Vec::with_capacity(new_listeners.len());
+    let mut invalid_ref_id: Vec<(String, i64)> = Vec::with_capacity(new_listeners.len());
+    let mut invalid_filter: Vec<InvalidFilter> = Vec::new();
+    let mut to_create: Vec<EventListener> = Vec::with_capacity(new_listeners.len());
+    let mut failed_check;
+
+    for listener in new_listeners {
+        failed_check = false;
+
+        if let Err(err) = check_endpoint(&listener.endpoint) {
+            invalid_endpoint.push(err);
+            failed_check = true;
+        }
+
+        let event_name = check_event_names(listener.event_name, &mut invalid_event_name);
+
+        if event_name.is_none() {
+            failed_check = true;
+        }
+
+        if !check_filter(&listener.filter, &mut invalid_filter) {
+            failed_check = true;
+        }
+
+        match listener.ref_table.as_str() {
+            "fs_items" => {
+                // listeners can only be attached to items the user owns
+                let check = conn.query_opt(
+                    "select users_id from fs_items where id = $1",
+                    &[&listener.ref_id]
+                ).await?;
+
+                let owned = check.map(|row| {
+                    let users_id: i64 = row.get(0);
+                    users_id == user.id || user.is_admin()
+                }).unwrap_or(false);
+
+                if !owned {
+                    invalid_ref_id.push((listener.ref_table.clone(), listener.ref_id));
+                    failed_check = true;
+                }
+            },
+            _ => {
+                invalid_ref_table.push(InvalidRefTable {
+                    ref_table: listener.ref_table.clone(),
+                    reason: "unknown ref table given".into()
+                });
+                failed_check = true;
+            }
+        }
+
+        if !failed_check {
+            to_create.push(EventListener {
+                id: uuid::Uuid::new_v4(),
+                event_name: event_name.unwrap(),
+                endpoint: listener.endpoint,
+                secret: new_secret()?,
+                ref_table: listener.ref_table,
+                ref_id: listener.ref_id,
+                users_id: user.id,
+                filter: listener.filter
+            });
+        }
+    }
+
+    if !invalid_endpoint.is_empty() || !invalid_event_name.is_empty() || !invalid_ref_table.is_empty() || !invalid_ref_id.is_empty() || !invalid_filter.is_empty() {
+        return JsonResponseBuilder::new(400)
+            .set_message("one or more given events are invalid")
+            .payload_response(json!({
+                "invalid_event_name": invalid_event_name,
+                "invalid_endpoint": invalid_endpoint,
+                "invalid_ref_table": invalid_ref_table,
+                "invalid_ref_id": invalid_ref_id,
+                "invalid_filter": invalid_filter
+            }));
+    }
+
+    let transaction = conn.transaction().await?;
+
+    for record in to_create.iter_mut() {
+        record.insert(&transaction).await?;
+    }
+
+    if !to_create.is_empty() {
+        let mut entry = audit.entry(Some(user.id), action::LISTENER_CREATED);
+        entry.set_target("event_listeners", to_create.iter().map(|v| v.id));
+        entry.insert(&transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    // the secret is not normally serialized so it is added here
+    let mut rtn = Vec::with_capacity(to_create.len());
+
+    for record in to_create {
+        let mut json = serde_json::to_value(&record)?;
+        json.as_object_mut().unwrap().insert("secret".into(), record.secret.into());
+
+        rtn.push(json);
+    }
+
+    JsonResponseBuilder::new(200).payload_response(rtn)
+}
+
+pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response> {
+    let mut conn = state.db.pool.get().await?;
+    let (user, _) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
+    let body = req.into_body();
+    let id_list: Vec<uuid::Uuid> = json_from_body(body).await?;
+    let transaction = conn.transaction().await?;
+
+    let deleted: Vec<uuid::Uuid> = transaction.query(
+        "\
+        delete from event_listeners \
+        where id = any($1) and \
+              users_id = $2 \
+        returning id",
+        &[&id_list, &user.id]
+    ).await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    let unknown: Vec<uuid::Uuid> = id_list.into_iter()
+        .filter(|id| !deleted.contains(id))
+        .collect();
+
+    if !unknown.is_empty() {
+        transaction.rollback().await?;
+
+        return JsonResponseBuilder::new(400)
+            .set_error("UnknownIdsGiven")
+            .set_message("some of the requested id were not found")
+            .payload_response(unknown);
+    }
+
+    if !deleted.is_empty() {
+        let mut entry = audit.entry(Some(user.id), action::LISTENER_DELETED);
+        entry.set_target("event_listeners", deleted);
+        entry.insert(&transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    JsonResponseBuilder::new(200).response()
+}
\ No newline at end of file
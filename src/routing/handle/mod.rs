@@ -1,21 +1,27 @@
-use crate::{http::{Request, error::Result, Response, response::{redirect_response, JsonResponseBuilder}}, components::{html::check_if_html_headers, auth::get_session}, state::AppState};
+use crate::{http::{Request, error::Result, Response, response::{redirect_response, JsonResponseBuilder}}, components::{html::check_if_html_headers, auth::take_session}, state::AppState};
 
 pub mod ping;
+pub mod status;
 
 pub mod auth;
 
 pub mod users;
+pub mod invites;
+pub mod register;
+pub mod quota;
+pub mod usage;
+pub mod audit;
 pub mod session;
 pub mod fs;
 pub mod sync;
+pub mod changes;
 pub mod listeners;
+pub mod events;
 pub mod _static_;
 
-pub async fn handle_get(mut req: Request) -> Result<Response> {
-    let state = AppState::from(&mut req);
-    let (head, _) = req.into_parts();
-    let conn = state.db.pool.get().await?;
-    let session = get_session(&*conn, &head.headers).await?;
+pub async fn handle_get(_state: AppState, req: Request) -> Result<Response> {
+    let (mut head, _) = req.into_parts();
+    let session = take_session(&mut head.extensions);
 
     if check_if_html_headers(&head.headers)? {
         if session.is_some() {
@@ -1,6 +1,6 @@
-use crate::http::{Request, error::Result, Response, response::JsonResponseBuilder};
+use crate::{http::{Request, error::Result, Response, response::JsonResponseBuilder}, state::AppState};
 
-pub async fn handle_get(_req: Request) -> Result<Response> {
+pub async fn handle_get(_state: AppState, _req: Request) -> Result<Response> {
     JsonResponseBuilder::new(200)
         .set_message("pong")
         .response()
@@ -0,0 +1,47 @@
+use serde_json::json;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response::JsonResponseBuilder,
+        uri::QueryMap
+    },
+    db::record::User,
+    components::{
+        auth::{require_session, require_self_or_admin},
+        quota::get_quota
+    },
+    state::AppState
+};
+
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+    let (session_user, _) = require_session(req.extensions_mut())?;
+    let query_map = QueryMap::new(req.uri());
+    let users_id: Option<i64> = query_map.get_parsed("users_id")?;
+
+    let user = if let Some(users_id) = users_id {
+        require_self_or_admin(&session_user, &users_id)?;
+
+        if users_id == session_user.id {
+            session_user
+        } else {
+            User::find_id(&*conn, &users_id).await?
+                .ok_or(Error::new(404, "UserNotFound", "requested user was not found"))?
+        }
+    } else {
+        session_user
+    };
+
+    let quota = get_quota(&state, &*conn, &user).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "users_id": quota.users_id,
+            "usage": quota.usage,
+            "limit": quota.limit,
+            "remaining": quota.remaining()
+        }))
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        body::json_from_body,
+        response::JsonResponseBuilder
+    },
+    db::record::UserInvite,
+    components::users::{check_available, create_user, create_email_verification, NewUser},
+    state::AppState
+};
+
+#[derive(Deserialize)]
+struct RegisterJson {
+    invite: String,
+    username: String,
+    password: String,
+    email: Option<String>
+}
+
+pub async fn handle_post(state: AppState, req: Request) -> Result<Response> {
+    let mut conn = state.db.pool.get().await?;
+    let (_, body) = req.into_parts();
+    let json: RegisterJson = json_from_body(body).await?;
+    let username = json.username.trim().to_owned();
+
+    if username.is_empty() {
+        return Err(Error::new(400, "InvalidUsername", "username cannot be empty"));
+    }
+
+    let transaction = conn.transaction().await?;
+
+    // lock the invite so that it cannot be used by another request at the
+    // same time
+    transaction.execute(
+        "select id from user_invites where id = $1 for update",
+        &[&json.invite]
+    ).await?;
+
+    let mut invite = UserInvite::find_id(&transaction, &json.invite).await?
+        .ok_or(Error::new(404, "InviteNotFound", "the given invite was not found"))?;
+
+    if !invite.is_valid() {
+        return Err(Error::new(400, "InviteExpired", "the given invite has expired or already been used"));
+    }
+
+    let email = if let Some(preset) = invite.email.clone() {
+        if let Some(given) = json.email {
+            if given.trim() != preset {
+                return Err(Error::new(400, "InviteEmailMismatch", "the given email does not match the invite"));
+            }
+        }
+
+        Some(preset)
+    } else if let Some(given) = json.email {
+        let given = given.trim().to_owned();
+
+        if given.is_empty() {
+            None
+        } else {
+            Some(given)
+        }
+    } else {
+        None
+    };
+
+    check_available(&transaction, &username, &email).await?;
+
+    let (user, root) = create_user(&state, &transaction, NewUser {
+        username,
+        password: json.password,
+        email,
+        role: invite.role.clone()
+    }).await?;
+
+    invite.mark_used(&transaction, &user.id).await?;
+
+    let verification = if state.email.is_enabled() && user.email.is_some() {
+        Some(create_email_verification(&transaction, &user).await?)
+    } else {
+        None
+    };
+
+    transaction.commit().await?;
+    root.committed();
+
+    if let Some(key_id) = verification {
+        let email_state = state.email.clone();
+        let to = user.email.clone().unwrap();
+
+        state.spawn_tracked(async move {
+            if let Err(err) = email_state.send_verification(&to, &key_id).await {
+                log::error!("failed to send verification email. {}", err);
+            }
+        });
+    }
+
+    JsonResponseBuilder::new(200)
+        .payload_response(user)
+}
\ No newline at end of file
@@ -1,3 +1,5 @@
+use uuid::Uuid;
+
 use crate::{
     http::{
         error::Result,
@@ -6,24 +8,18 @@ use crate::{
         Request
     }, 
     db::record::UserSession, 
-    components::{auth::{require_session, login_redirect}, html::{check_if_html_headers, response_index_html_parts}},
+    components::{
+        auth::require_session,
+        audit::{AuditContext, action}
+    },
     state::AppState
 };
 
 pub mod session_id;
 
-pub async fn handle_get(state: AppState, req: Request) -> Result<Response> {
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
     let conn = state.db.pool.get().await?;
-    let session_check = require_session(&*conn, req.headers()).await;
-
-    if check_if_html_headers(req.headers())? {
-        return match session_check {
-            Ok(_) => response_index_html_parts(state.template),
-            Err(_) => login_redirect(req.uri())
-        }
-    }
-
-    let (user, session) = session_check?;
+    let (user, session) = require_session(req.extensions_mut())?;
     let user_sessions = UserSession::find_users_id(
         &*conn,
         &user.id,
@@ -34,15 +30,26 @@ pub async fn handle_get(state: AppState, req: Request) -> Result<Response> {
         .payload_response(user_sessions)
 }
 
-pub async fn handle_delete(state: AppState, req: Request) -> Result<Response> {
+pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response> {
     let conn = state.db.pool.get().await?;
-    let (user, session) = require_session(&*conn, req.headers()).await?;
+    let (user, session) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
 
-    conn.execute(
+    let revoked: Vec<Uuid> = conn.query(
         "\
-        delete from user_session where users_id = $1 and token != $2",
+        delete from user_sessions where users_id = $1 and token != $2 \
+        returning token",
         &[&user.id, &session.token]
-    ).await?;
+    ).await?
+        .iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    if !revoked.is_empty() {
+        let mut entry = audit.entry(Some(user.id), action::SESSION_REVOKED);
+        entry.set_target("user_sessions", revoked);
+        entry.insert(&*conn).await?;
+    }
 
     JsonResponseBuilder::new(200)
         .response()
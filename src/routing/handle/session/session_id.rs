@@ -7,14 +7,18 @@ use crate::{
         error::{Result, Error},
         response::JsonResponseBuilder,
     },
-    components::auth::require_session,
+    components::{
+        auth::require_session,
+        audit::{AuditContext, action}
+    },
     state::AppState, routing::Params
 };
 
 pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response> {
     let params = req.extensions_mut().remove::<Params>().unwrap();
     let conn = state.db.pool.get().await?;
-    let (user, session) = require_session(&*conn, req.headers()).await?;
+    let (user, session) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
     let token: Uuid;
 
     if let Some(given) = params.get_value_ref("session_id") {
@@ -32,13 +36,17 @@ pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response
     }
 
     let result = conn.execute(
-        "delete from user_session where users_id = $1 and token = $2",
+        "delete from user_sessions where users_id = $1 and token = $2",
         &[&user.id, &token]
     ).await?;
 
     if result != 1 {
         Err(Error::new(404, "SessionIdNotFound", "given session id was not found"))
     } else {
+        let mut entry = audit.entry(Some(user.id), action::SESSION_REVOKED);
+        entry.set_target("user_sessions", [token]);
+        entry.insert(&*conn).await?;
+
         JsonResponseBuilder::new(200)
             .response()
     }
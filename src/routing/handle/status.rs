@@ -0,0 +1,25 @@
+use serde_json::json;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::Result,
+        response::JsonResponseBuilder
+    },
+    state::AppState
+};
+
+pub async fn handle_get(state: AppState, _req: Request) -> Result<Response> {
+    // the pool is only missing once shutdown has closed it
+    let db_pool = state.db.pool.state().map(|pool| json!({
+        "connections": pool.connections,
+        "idle_connections": pool.idle_connections
+    }));
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "connections": state.connections.status(),
+            "db_pool": db_pool
+        }))
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use serde_json::json;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        response::JsonResponseBuilder,
+        uri::QueryMap
+    },
+    db::record::{User, FsItem, FsItemType, DirStats},
+    components::{
+        auth::{require_session, require_self_or_admin},
+        quota::get_quota
+    },
+    state::AppState
+};
+
+const DEFAULT_LIMIT: i64 = 10;
+const MAX_LIMIT: i64 = 100;
+
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+    let (session_user, _) = require_session(req.extensions_mut())?;
+    let query_map = QueryMap::new(req.uri());
+    let users_id: Option<i64> = query_map.get_parsed("users_id")?;
+    let limit: i64 = query_map.get_parsed("limit")?.unwrap_or(DEFAULT_LIMIT);
+    let directory = query_map.get_value("directory").flatten();
+
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(Error::new(400, "InvalidLimit", format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let user = if let Some(users_id) = users_id {
+        require_self_or_admin(&session_user, &users_id)?;
+
+        if users_id == session_user.id {
+            session_user
+        } else {
+            User::find_id(&*conn, &users_id).await?
+                .ok_or(Error::new(404, "UserNotFound", "requested user was not found"))?
+        }
+    } else {
+        session_user
+    };
+
+    let parent = if let Some(path) = directory {
+        FsItem::find_path(&*conn, &user.id, &path).await?
+    } else {
+        FsItem::find_user_root(&*conn, &user.id).await?
+    };
+
+    let parent = match parent {
+        Some(item) if item.item_exists => item,
+        _ => return Err(Error::new(404, "PathNotFound", "requested path was not found"))
+    };
+
+    if parent.item_type != FsItemType::Dir {
+        return Err(Error::new(400, "NotADirectory", "directory usage can only be listed for directories"));
+    }
+
+    let quota = get_quota(&state, &*conn, &user).await?;
+    let directories = DirStats::find_largest(&*conn, &parent.id, &limit).await?;
+    let files = FsItem::find_largest_files(&*conn, &user.id, &limit).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "users_id": user.id,
+            "usage": quota.usage,
+            "limit": quota.limit,
+            "directory": parent.id,
+            "directories": directories,
+            "files": files
+        }))
+}
\ No newline at end of file
@@ -1,5 +1,5 @@
 use serde::Deserialize;
-use tokio::fs::create_dir;
+use serde_json::json;
 
 use crate::{
     http::{
@@ -7,96 +7,166 @@ use crate::{
         Response,
         error::{Result, Error},
         body::json_from_body,
-        response::JsonResponseBuilder
+        response::JsonResponseBuilder,
+        uri::QueryMap
     },
-    db::record::{
-        User,
-        FsItemType
+    db::record::{User, UserRole},
+    components::{
+        auth::{take_session, require_session, require_admin},
+        audit::{AuditContext, action},
+        users::{delete_user, create_user, check_available, NewUser}
     },
-    components::auth::require_session,
-    security::argon::hash_with_default,
     state::AppState
 };
 
 pub mod users_id;
 
+const DEFAULT_LIMIT: i64 = 25;
+const MAX_LIMIT: i64 = 100;
+
+pub async fn handle_get(state: AppState, req: Request) -> Result<Response> {
+    let conn = state.db.pool.get().await?;
+    let query_map = QueryMap::new(req.uri());
+    let limit: i64 = query_map.get_parsed("limit")?.unwrap_or(DEFAULT_LIMIT);
+    let offset: i64 = query_map.get_parsed("offset")?.unwrap_or(0);
+
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(Error::new(400, "InvalidLimit", format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    if offset < 0 {
+        return Err(Error::new(400, "InvalidOffset", "offset cannot be negative"));
+    }
+
+    let total = User::count(&*conn).await?;
+    let users = User::find_list(&*conn, &limit, &offset).await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(json!({
+            "total": total,
+            "limit": limit,
+            "offset": offset,
+            "users": users
+        }))
+}
+
+pub async fn handle_delete(state: AppState, req: Request) -> Result<Response> {
+    let mut conn = state.db.pool.get().await?;
+    let (mut head, body) = req.into_parts();
+    let (session_user, _) = require_session(&mut head.extensions)?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
+
+    let query_map = QueryMap::new(&head.uri);
+    let transfer_to: Option<i64> = query_map.get_parsed("transfer_to")?;
+    let id_list: Vec<i64> = json_from_body(body).await?;
+
+    if let Some(transfer_id) = transfer_to.as_ref() {
+        if id_list.contains(transfer_id) {
+            return Err(Error::new(400, "InvalidTransfer", "cannot transfer to a user that is being deleted"));
+        }
+    }
+
+    let transfer = if let Some(transfer_id) = transfer_to {
+        Some(User::find_id(&*conn, &transfer_id).await?.ok_or(
+            Error::new(404, "TransferUserNotFound", "the requested transfer user was not found")
+        )?)
+    } else {
+        None
+    };
+
+    let mut unknown = Vec::with_capacity(id_list.len());
+    let mut to_delete = Vec::with_capacity(id_list.len());
+
+    for id in id_list {
+        if let Some(user) = User::find_id(&*conn, &id).await? {
+            to_delete.push(user);
+        } else {
+            unknown.push(id);
+        }
+    }
+
+    if !unknown.is_empty() {
+        return JsonResponseBuilder::new(400)
+            .set_error("UnknownIdsGiven")
+            .set_message("some of the requested id were not found")
+            .payload_response(unknown);
+    }
+
+    let transaction = conn.transaction().await?;
+    let admin_count = User::count_role(&transaction, UserRole::Admin).await?;
+    let deleted_admins = to_delete.iter().filter(|u| u.is_admin()).count() as i64;
+
+    if deleted_admins >= admin_count {
+        return Err(Error::new(400, "LastAdministrator", "at least one administrator must remain"));
+    }
+
+    let mut root_actions = Vec::with_capacity(to_delete.len());
+
+    for user in to_delete.iter() {
+        if let Some(root_action) = delete_user(&state, &transaction, user, transfer.as_ref()).await? {
+            root_actions.push(root_action);
+        }
+    }
+
+    let mut entry = audit.entry(Some(session_user.id), action::USER_DELETED);
+    entry.set_target("users", to_delete.iter().map(|u| u.id));
+    entry.details = json!({
+        "transfer_to": transfer.as_ref().map(|u| u.id)
+    });
+    entry.insert(&transaction).await?;
+
+    transaction.commit().await?;
+
+    for root_action in root_actions {
+        root_action.apply(&state).await;
+    }
+
+    JsonResponseBuilder::new(200)
+        .response()
+}
+
 #[derive(Deserialize)]
 struct NewUserJson {
     username: String,
     password: String,
-    email: Option<String>
+    email: Option<String>,
+    role: Option<UserRole>
 }
 
 pub async fn handle_post(app: AppState, req: Request) -> Result<Response> {
     let mut conn = app.db.pool.get().await?;
-    let (head, body) = req.into_parts();
-    let (_,_) = require_session(&*conn, &head.headers).await?;
+    let (mut head, body) = req.into_parts();
+    let session = take_session(&mut head.extensions);
     let new_user: NewUserJson = json_from_body(body).await?;
 
-    let existing = User::find_username_or_optional_email(&*conn, &new_user.username, &new_user.email).await?;
-
-    if existing.len() != 0 {
-        for record in existing {
-            if record.username == new_user.username {
-                return Err(Error::new(400, "UsernameInUse", "the requested username is already in use"))
-            } else {
-                return Err(Error::new(400, "EmailInUse", "the requested email is already in use"))
-            }
-        }
-    }
+    check_available(&*conn, &new_user.username, &new_user.email).await?;
 
     let transaction = conn.transaction().await?;
-    let user = {
-        let id = app.snowflakes.users.next_id().await?;
-        let hash = hash_with_default(&new_user.password)?;
-
-        transaction.query(
-            "\
-            insert into users (id, username, hash, email) values \
-            ($1, $2, $3, $4)",
-            &[&id, &new_user.username, &hash, &new_user.email]
-        ).await?;
-
-        User {
-            id,
-            username: new_user.username,
-            hash,
-            email: new_user.email,
-            email_verified: false,
-            totp_enabled: false,
-            totp_algorithm: None,
-            totp_secret: None,
-            totp_step: None,
-            totp_digits: None
-        }
-    };
 
-    {
-        let id = app.snowflakes.fs_items.next_id().await?;
-        let item_type: i16 = FsItemType::Dir.into();
-        let parent = None::<i64>;
-        let directory = "".to_owned();
-        let basename = user.id.to_string();
-        let created = chrono::Utc::now();
-        let modified = None::<chrono::DateTime<chrono::Utc>>;
-        let is_root = true;
-
-        transaction.query(
-            "\
-            insert into fs_items (id, item_type, parent, users_id, directory, basename, created, modified, is_root) values \
-            ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
-            &[&id, &item_type, &parent, &user.id, &directory, &basename, &created, &modified, &is_root]
-        ).await?;
-    }
+    // prevent multiple requests from bootstrapping at the same time
+    transaction.execute("lock table users in share row exclusive mode", &[]).await?;
 
-    {
-        let mut root_path = app.storage.directory.clone();
-        root_path.push(user.id.to_string());
+    // when there are no users the first account created will be an
+    // administrator and no session is required
+    let role = if User::count(&transaction).await? == 0 {
+        UserRole::Admin
+    } else if let Some((session_user, _)) = session {
+        require_admin(&session_user)?;
 
-        create_dir(root_path).await?;
-    }
+        new_user.role.unwrap_or(UserRole::User)
+    } else {
+        return Err(Error::new(401, "NoSession", "no session is available"));
+    };
+
+    let (user, root) = create_user(&app, &transaction, NewUser {
+        username: new_user.username,
+        password: new_user.password,
+        email: new_user.email,
+        role
+    }).await?;
 
     transaction.commit().await?;
+    root.committed();
 
     JsonResponseBuilder::new(200)
         .payload_response(user)
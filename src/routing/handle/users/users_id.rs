@@ -0,0 +1,236 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    http::{
+        Request,
+        Response,
+        error::{Result, Error},
+        body::{json_from_body, deserialize_some},
+        response::JsonResponseBuilder,
+        uri::QueryMap
+    },
+    db::record::{User, UserRole},
+    components::{
+        auth::{require_session, require_admin, require_self_or_admin},
+        audit::{AuditContext, action},
+        users::delete_user
+    },
+    routing::Params,
+    state::AppState
+};
+
+fn get_users_id(params: &Params) -> Result<i64> {
+    if let Some(given) = params.get_value_ref("users_id") {
+        if let Ok(parsed) = given.parse() {
+            Ok(parsed)
+        } else {
+            Err(Error::new(400, "InvalidUsersId", "given users id is not a valid integer"))
+        }
+    } else {
+        Err(Error::new(400, "MissingUsersId", "no users id was given"))
+    }
+}
+
+pub async fn handle_get(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let conn = state.db.pool.get().await?;
+    let (session_user, _) = require_session(req.extensions_mut())?;
+    let users_id = get_users_id(&params)?;
+    require_self_or_admin(&session_user, &users_id)?;
+
+    if let Some(user) = User::find_id(&*conn, &users_id).await? {
+        JsonResponseBuilder::new(200)
+            .payload_response(user)
+    } else {
+        Err(Error::new(404, "UserNotFound", "requested user was not found"))
+    }
+}
+
+#[derive(Deserialize)]
+struct UpdateUserJson {
+    username: Option<String>,
+    email: Option<String>,
+    role: Option<UserRole>,
+
+    // null will remove the quota and fallback to the default
+    #[serde(default, deserialize_with = "deserialize_some")]
+    quota: Option<Option<i64>>
+}
+
+pub async fn handle_put(state: AppState, req: Request) -> Result<Response> {
+    let (mut head, body) = req.into_parts();
+    let params = head.extensions.remove::<Params>().unwrap();
+    let mut conn = state.db.pool.get().await?;
+    let (session_user, _) = require_session(&mut head.extensions)?;
+    let users_id = get_users_id(&params)?;
+    require_self_or_admin(&session_user, &users_id)?;
+    let audit = AuditContext::new(&head.headers, &head.extensions);
+
+    let json: UpdateUserJson = json_from_body(body).await?;
+    let mut changed = Vec::new();
+
+    let mut user = User::find_id(&*conn, &users_id).await?
+        .ok_or(Error::new(404, "UserNotFound", "requested user was not found"))?;
+
+    if let Some(username) = json.username {
+        let username = username.trim().to_owned();
+
+        if username.is_empty() {
+            return Err(Error::new(400, "InvalidUsername", "username cannot be empty"));
+        }
+
+        if username != user.username {
+            if User::find_username(&*conn, &username).await?.is_some() {
+                return Err(Error::new(400, "UsernameInUse", "the requested username is already in use"));
+            }
+
+            user.username = username;
+            changed.push("username");
+        }
+    }
+
+    if let Some(email) = json.email {
+        // an empty string will remove the email from the user
+        let email = email.trim().to_owned();
+        let email = if email.is_empty() {
+            None
+        } else {
+            Some(email)
+        };
+
+        if email != user.email {
+            if email.is_some() {
+                let existing = User::find_username_or_optional_email(&*conn, &user.username, &email).await?;
+
+                for record in existing {
+                    if record.id != user.id && record.email == email {
+                        return Err(Error::new(400, "EmailInUse", "the requested email is already in use"));
+                    }
+                }
+            }
+
+            user.email = email;
+            user.email_verified = false;
+            changed.push("email");
+        }
+    }
+
+    let transaction = conn.transaction().await?;
+
+    if let Some(role) = json.role {
+        if role != user.role {
+            require_admin(&session_user)?;
+
+            if user.is_admin() && User::count_role(&transaction, UserRole::Admin).await? <= 1 {
+                return Err(Error::new(400, "LastAdministrator", "at least one administrator must remain"));
+            }
+
+            user.role = role;
+            changed.push("role");
+        }
+    }
+
+    if let Some(quota) = json.quota {
+        require_admin(&session_user)?;
+
+        if let Some(bytes) = quota.as_ref() {
+            if *bytes < 0 {
+                return Err(Error::new(400, "InvalidQuota", "quota cannot be negative"));
+            }
+        }
+
+        if quota != user.quota {
+            user.quota = quota;
+            changed.push("quota");
+        }
+    }
+
+    let role_value: i16 = user.role.clone().into();
+
+    transaction.execute(
+        "\
+        update users \
+        set username = $2, \
+            email = $3, \
+            email_verified = $4, \
+            role = $5, \
+            quota = $6 \
+        where id = $1",
+        &[&user.id, &user.username, &user.email, &user.email_verified, &role_value, &user.quota]
+    ).await?;
+
+    if !user.email_verified {
+        transaction.execute(
+            "delete from email_verificiations where users_id = $1",
+            &[&user.id]
+        ).await?;
+    }
+
+    if !changed.is_empty() {
+        let mut entry = audit.entry(Some(session_user.id), action::USER_UPDATED);
+        entry.set_target("users", [user.id]);
+        entry.details = json!({
+            "changed": changed,
+            "role": user.role,
+            "quota": user.quota
+        });
+        entry.insert(&transaction).await?;
+    }
+
+    transaction.commit().await?;
+
+    JsonResponseBuilder::new(200)
+        .payload_response(user)
+}
+
+pub async fn handle_delete(state: AppState, mut req: Request) -> Result<Response> {
+    let params = req.extensions_mut().remove::<Params>().unwrap();
+    let mut conn = state.db.pool.get().await?;
+    let (session_user, _) = require_session(req.extensions_mut())?;
+    let audit = AuditContext::new(req.headers(), req.extensions());
+
+    let users_id = get_users_id(&params)?;
+    let query_map = QueryMap::new(req.uri());
+    let transfer_to: Option<i64> = query_map.get_parsed("transfer_to")?;
+
+    let user = User::find_id(&*conn, &users_id).await?
+        .ok_or(Error::new(404, "UserNotFound", "requested user was not found"))?;
+
+    let transfer = if let Some(transfer_id) = transfer_to {
+        if transfer_id == user.id {
+            return Err(Error::new(400, "InvalidTransfer", "cannot transfer to a user that is being deleted"));
+        }
+
+        Some(User::find_id(&*conn, &transfer_id).await?.ok_or(
+            Error::new(404, "TransferUserNotFound", "the requested transfer user was not found")
+        )?)
+    } else {
+        None
+    };
+
+    let transaction = conn.transaction().await?;
+
+    if user.is_admin() && User::count_role(&transaction, UserRole::Admin).await? <= 1 {
+        return Err(Error::new(400, "LastAdministrator", "at least one administrator must remain"));
+    }
+
+    let root_action = delete_user(&state, &transaction, &user, transfer.as_ref()).await?;
+
+    let mut entry = audit.entry(Some(session_user.id), action::USER_DELETED);
+    entry.set_target("users", [user.id]);
+    entry.details = json!({
+        "username": user.username,
+        "transfer_to": transfer.as_ref().map(|u| u.id)
+    });
+    entry.insert(&transaction).await?;
+
+    transaction.commit().await?;
+
+    if let Some(root_action) = root_action {
+        root_action.apply(&state).await;
+    }
+
+    JsonResponseBuilder::new(200)
+        .response()
+}
\ No newline at end of file
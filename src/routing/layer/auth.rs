@@ -0,0 +1,123 @@
+use std::pin::Pin;
+use std::future::Future;
+
+use tower::{Layer, Service};
+
+use crate::{
+    http::{self, error::Error},
+    components::{
+        auth::{get_session, require_admin, login_redirect, SessionTuple},
+        html::{check_if_html_headers, response_index_html_parts}
+    },
+    state::AppState
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    // no session lookup is done
+    Public,
+    // the session is looked up but not required
+    Optional,
+    Session,
+    Admin
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AuthRequirement {
+    pub access: Access,
+    // requests accepting html get the index page when a session is present
+    // and are redirected to the login page otherwise
+    pub page: bool
+}
+
+pub struct AuthLayer {
+    state: AppState,
+    requirement: AuthRequirement
+}
+
+impl AuthLayer {
+    pub fn new(state: AppState, requirement: AuthRequirement) -> AuthLayer {
+        AuthLayer { state, requirement }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService {
+            inner,
+            state: self.state.clone(),
+            requirement: self.requirement
+        }
+    }
+}
+
+pub struct AuthService<S> {
+    inner: S,
+    state: AppState,
+    requirement: AuthRequirement
+}
+
+impl<S> Service<http::Request> for AuthService<S>
+where
+    S: Service<
+        http::Request,
+        Response = http::Response,
+        Error = http::error::Error,
+        Future = Pin<Box<dyn Future<Output = http::error::Result<http::Response>> + Send>>
+    > + Clone + Send + 'static
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request) -> Self::Future {
+        if self.requirement.access == Access::Public {
+            return self.inner.call(req);
+        }
+
+        // the inner service was driven to ready so that one is used and the
+        // clone is left in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let state = self.state.clone();
+        let requirement = self.requirement;
+
+        Box::pin(async move {
+            // the connection is returned to the pool before the handler runs
+            let session_check = {
+                let conn = state.db.pool.get().await?;
+
+                get_session(&*conn, req.headers()).await
+            };
+
+            if requirement.page && check_if_html_headers(req.headers())? {
+                return match session_check {
+                    Ok(Some(_)) => response_index_html_parts(state.template),
+                    _ => login_redirect(req.uri())
+                };
+            }
+
+            let session = session_check?;
+
+            if requirement.access != Access::Optional {
+                if let Some((user, _)) = session.as_ref() {
+                    if requirement.access == Access::Admin {
+                        require_admin(user)?;
+                    }
+                } else {
+                    return Err(Error::new(401, "NoSession", "no session is available"));
+                }
+            }
+
+            req.extensions_mut().insert(session.map(|(user, session)| SessionTuple(user, session)));
+
+            inner.call(req).await
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,282 @@
+use std::io;
+use std::pin::Pin;
+use std::future::Future;
+
+use async_compression::Level;
+use async_compression::tokio::bufread::{BrotliEncoder, GzipEncoder, ZlibEncoder, ZstdEncoder};
+use futures::TryStreamExt;
+use hyper::{Body, Method, StatusCode};
+use hyper::body::HttpBody;
+use hyper::header::{
+    HeaderMap,
+    HeaderValue,
+    ACCEPT_ENCODING,
+    ACCEPT_RANGES,
+    CONTENT_ENCODING,
+    CONTENT_LENGTH,
+    CONTENT_TYPE,
+    VARY
+};
+use tokio_util::io::{ReaderStream, StreamReader};
+use tower::{Layer, Service};
+
+use crate::config::CompressionConfig;
+use crate::http::{self, mime::is_compressible};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Brotli,
+    Zstd,
+    Gzip,
+    Deflate
+}
+
+impl Encoding {
+    // in order of preference when the client gives them the same weight
+    const ALL: [Encoding; 4] = [Encoding::Brotli, Encoding::Zstd, Encoding::Gzip, Encoding::Deflate];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate"
+        }
+    }
+}
+
+// picks the supported encoding with the highest weight in accept-encoding.
+// anything given a weight of 0 is not acceptable
+fn negotiate(headers: &HeaderMap) -> Option<Encoding> {
+    let mut weights: [Option<f32>; 4] = [None; 4];
+    let mut wildcard: Option<f32> = None;
+
+    for value in headers.get_all(ACCEPT_ENCODING) {
+        let value = match value.to_str() {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+
+        for item in value.split(',') {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap().trim();
+            let mut weight = 1.0;
+
+            for param in parts {
+                if let Some(given) = param.trim().strip_prefix("q=") {
+                    weight = given.trim().parse().unwrap_or(0.0);
+                }
+            }
+
+            if name == "*" {
+                wildcard = Some(weight);
+                continue;
+            }
+
+            for (index, encoding) in Encoding::ALL.iter().enumerate() {
+                if name.eq_ignore_ascii_case(encoding.as_str()) {
+                    weights[index] = Some(weight);
+                }
+            }
+        }
+    }
+
+    let mut selected: Option<(Encoding, f32)> = None;
+
+    for (index, encoding) in Encoding::ALL.iter().enumerate() {
+        let weight = weights[index].or(wildcard).unwrap_or(0.0);
+
+        if weight > 0.0 && selected.map(|(_, best)| weight > best).unwrap_or(true) {
+            selected = Some((*encoding, weight));
+        }
+    }
+
+    selected.map(|(encoding, _)| encoding)
+}
+
+// the body is compressed as it is read so large files are never held in
+// memory
+fn compress_body(body: Body, encoding: Encoding) -> Body {
+    let reader = StreamReader::new(TryStreamExt::map_err(body, io::Error::other));
+
+    match encoding {
+        // the default brotli quality is too slow to do on the fly
+        Encoding::Brotli => Body::wrap_stream(ReaderStream::new(
+            BrotliEncoder::with_quality(reader, Level::Precise(4))
+        )),
+        Encoding::Zstd => Body::wrap_stream(ReaderStream::new(ZstdEncoder::new(reader))),
+        Encoding::Gzip => Body::wrap_stream(ReaderStream::new(GzipEncoder::new(reader))),
+        Encoding::Deflate => Body::wrap_stream(ReaderStream::new(ZlibEncoder::new(reader)))
+    }
+}
+
+fn compress_response(res: http::Response, encoding: Option<Encoding>, min_size: u64) -> http::Response {
+    let (mut parts, body) = res.into_parts();
+    let compressible = parts.headers.get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .map(|mime| is_compressible(&mime))
+        .unwrap_or(false);
+
+    if !compressible {
+        return http::Response::from_parts(parts, body);
+    }
+
+    // caches need to know that the response depends on accept-encoding even
+    // when it is sent as is
+    parts.headers.append(VARY, HeaderValue::from_static("accept-encoding"));
+
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None => return http::Response::from_parts(parts, body)
+    };
+
+    if parts.status.is_informational() ||
+        parts.status == StatusCode::NO_CONTENT ||
+        parts.status == StatusCode::NOT_MODIFIED ||
+        parts.status == StatusCode::PARTIAL_CONTENT ||
+        parts.headers.contains_key(CONTENT_ENCODING) {
+        return http::Response::from_parts(parts, body);
+    }
+
+    let size = body.size_hint().exact().or_else(|| {
+        parts.headers.get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+    });
+
+    if let Some(size) = size {
+        if size < min_size {
+            return http::Response::from_parts(parts, body);
+        }
+    }
+
+    parts.headers.remove(CONTENT_LENGTH);
+    // ranges would refer to the uncompressed bytes
+    parts.headers.remove(ACCEPT_RANGES);
+    parts.headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding.as_str()));
+
+    http::Response::from_parts(parts, compress_body(body, encoding))
+}
+
+pub struct CompressionLayer {
+    config: CompressionConfig
+}
+
+impl CompressionLayer {
+    pub fn new(config: CompressionConfig) -> CompressionLayer {
+        CompressionLayer { config }
+    }
+}
+
+impl<S> Layer<S> for CompressionLayer {
+    type Service = CompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CompressionService {
+            inner,
+            config: self.config.clone()
+        }
+    }
+}
+
+pub struct CompressionService<S> {
+    inner: S,
+    config: CompressionConfig
+}
+
+impl<S> Service<http::Request> for CompressionService<S>
+where
+    S: Service<
+        http::Request,
+        Response = http::Response,
+        Error = http::error::Error,
+        Future = Pin<Box<dyn Future<Output = http::error::Result<http::Response>> + Send>>
+    >
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request) -> Self::Future {
+        if !self.config.enable {
+            return self.inner.call(req);
+        }
+
+        let encoding = if req.method() != Method::HEAD {
+            negotiate(req.headers())
+        } else {
+            None
+        };
+        let min_size = self.config.min_size;
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let res = future.await?;
+
+            Ok(compress_response(res, encoding, min_size))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(values: &[&str]) -> Option<Encoding> {
+        let mut headers = HeaderMap::new();
+
+        for value in values {
+            headers.append(ACCEPT_ENCODING, HeaderValue::from_str(value).unwrap());
+        }
+
+        negotiate(&headers)
+    }
+
+    #[test]
+    fn no_header_or_unsupported() {
+        assert_eq!(check(&[]), None);
+        assert_eq!(check(&["identity"]), None);
+        assert_eq!(check(&["compress, identity"]), None);
+    }
+
+    #[test]
+    fn weights() {
+        assert_eq!(check(&["gzip"]), Some(Encoding::Gzip));
+        assert_eq!(check(&["gzip;q=1.0, br;q=0.5"]), Some(Encoding::Gzip));
+        assert_eq!(check(&["deflate;q=0.9, gzip; q=0.8"]), Some(Encoding::Deflate));
+        assert_eq!(check(&["br;q=0.2", "zstd;q=0.4"]), Some(Encoding::Zstd));
+    }
+
+    #[test]
+    fn ties_use_preference() {
+        assert_eq!(check(&["gzip, br"]), Some(Encoding::Brotli));
+        assert_eq!(check(&["deflate, gzip, zstd"]), Some(Encoding::Zstd));
+        assert_eq!(check(&["gzip;q=0.5, deflate;q=0.5"]), Some(Encoding::Gzip));
+    }
+
+    #[test]
+    fn zero_weight_is_not_acceptable() {
+        assert_eq!(check(&["br;q=0"]), None);
+        assert_eq!(check(&["gzip;q=0, deflate"]), Some(Encoding::Deflate));
+        assert_eq!(check(&["gzip;q=nope"]), None);
+    }
+
+    #[test]
+    fn wildcard() {
+        assert_eq!(check(&["*"]), Some(Encoding::Brotli));
+        assert_eq!(check(&["*, br;q=0"]), Some(Encoding::Zstd));
+        assert_eq!(check(&["gzip, *;q=0"]), Some(Encoding::Gzip));
+        assert_eq!(check(&["*;q=0"]), None);
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(check(&["GZIP"]), Some(Encoding::Gzip));
+        assert_eq!(check(&["Br;q=0.5, Deflate;q=0.4"]), Some(Encoding::Brotli));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,189 @@
+use std::pin::Pin;
+use std::future::Future;
+use std::sync::Arc;
+
+use hyper::{Body, Method};
+use hyper::header::{
+    HeaderMap,
+    HeaderValue,
+    ORIGIN,
+    VARY,
+    ACCESS_CONTROL_ALLOW_ORIGIN,
+    ACCESS_CONTROL_ALLOW_CREDENTIALS,
+    ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_HEADERS,
+    ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_METHOD
+};
+use tower::{Layer, Service};
+
+use crate::config::CorsConfig;
+use crate::http::{self, response::{build, JsonResponseBuilder}};
+
+// header values are built once from the config and shared by every
+// connection
+struct CorsPolicy {
+    any_origin: bool,
+    origins: Vec<String>,
+    methods: HeaderValue,
+    headers: HeaderValue,
+    credentials: bool,
+    max_age: Option<HeaderValue>
+}
+
+impl CorsPolicy {
+    fn new(config: &CorsConfig) -> CorsPolicy {
+        // the config has already checked that these are valid header values
+        CorsPolicy {
+            any_origin: config.origins.iter().any(|origin| origin == "*"),
+            origins: config.origins.clone(),
+            methods: HeaderValue::from_str(&config.methods.join(", ")).unwrap(),
+            headers: HeaderValue::from_str(&config.headers.join(", ")).unwrap(),
+            credentials: config.credentials,
+            max_age: config.max_age.map(HeaderValue::from)
+        }
+    }
+
+    fn allows(&self, origin: &str) -> bool {
+        self.any_origin || self.origins.iter().any(|allowed| allowed == origin)
+    }
+
+    // responses change depending on the origin unless every origin is
+    // allowed. caches need to know that even when the origin is rejected
+    fn vary(&self, headers: &mut HeaderMap) {
+        if !self.any_origin {
+            headers.append(VARY, HeaderValue::from_static("origin"));
+        }
+    }
+
+    // the config does not allow "*" with credentials so the origin only needs
+    // to be sent back for a list of origins
+    fn decorate(&self, headers: &mut HeaderMap, origin: &HeaderValue) {
+        if self.any_origin {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+        } else {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+        }
+
+        if self.credentials {
+            headers.insert(ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+    }
+
+    fn preflight(&self, origin: &HeaderValue) -> http::error::Result<http::Response> {
+        let mut res = build()
+            .status(204)
+            .body(Body::empty())?;
+        let headers = res.headers_mut();
+
+        self.decorate(headers, origin);
+        headers.insert(ACCESS_CONTROL_ALLOW_METHODS, self.methods.clone());
+        headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, self.headers.clone());
+
+        if let Some(max_age) = self.max_age.as_ref() {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+
+        Ok(res)
+    }
+}
+
+#[derive(Clone)]
+pub struct CorsLayer {
+    policy: Option<Arc<CorsPolicy>>
+}
+
+impl CorsLayer {
+    pub fn new(config: &CorsConfig) -> CorsLayer {
+        CorsLayer {
+            policy: if config.enable {
+                Some(Arc::new(CorsPolicy::new(config)))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for CorsLayer {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorsService {
+            inner,
+            policy: self.policy.clone()
+        }
+    }
+}
+
+pub struct CorsService<S> {
+    inner: S,
+    policy: Option<Arc<CorsPolicy>>
+}
+
+impl<S> Service<http::Request> for CorsService<S>
+where
+    S: Service<
+        http::Request,
+        Response = http::Response,
+        Error = http::error::Error,
+        Future = Pin<Box<dyn Future<Output = http::error::Result<http::Response>> + Send>>
+    >
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request) -> Self::Future {
+        let policy = match self.policy.as_ref() {
+            Some(policy) => policy.clone(),
+            None => return self.inner.call(req)
+        };
+        // same origin requests do not always send an origin and only get
+        // the vary header
+        let origin = req.headers().get(ORIGIN).cloned();
+        let allowed = origin.as_ref()
+            .and_then(|v| v.to_str().ok())
+            .map(|v| policy.allows(v))
+            .unwrap_or(false);
+
+        if let Some(origin) = origin.as_ref() {
+            if req.method() == Method::OPTIONS && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD) {
+                let origin = origin.clone();
+
+                return Box::pin(async move {
+                    let mut res = if allowed {
+                        policy.preflight(&origin)?
+                    } else {
+                        JsonResponseBuilder::new(403)
+                            .set_error("OriginNotAllowed")
+                            .set_message("requests from the given origin are not allowed")
+                            .response()?
+                    };
+
+                    policy.vary(res.headers_mut());
+
+                    Ok(res)
+                });
+            }
+        }
+
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            let mut res = future.await?;
+
+            if let Some(origin) = origin.as_ref().filter(|_| allowed) {
+                policy.decorate(res.headers_mut(), origin);
+            }
+
+            policy.vary(res.headers_mut());
+
+            Ok(res)
+        })
+    }
+}
\ No newline at end of file
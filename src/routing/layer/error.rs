@@ -58,7 +58,7 @@ where
                     log::info!("error response: {}", error);
                 }
 
-                JsonResponseBuilder::new(error.status_ref().clone())
+                JsonResponseBuilder::new(*error.status_ref())
                     .set_error(error.name_str())
                     .set_message(error.message_str())
                     .response()
@@ -0,0 +1,120 @@
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Duration;
+
+use hyper::Body;
+use hyper::header::CONTENT_LENGTH;
+use tower::{Layer, Service};
+
+use crate::config::{BodyConfig, BodyLimitConfig};
+use crate::http::{self, body::{limit_body, BodyError}, error::Error};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BodyKind {
+    Json,
+    Upload
+}
+
+fn apply_limit(body: Body, limit: BodyLimitConfig) -> Body {
+    limit_body(
+        body,
+        limit.max_size,
+        limit.idle_timeout.map(Duration::from_secs),
+        limit.timeout.map(Duration::from_secs)
+    )
+}
+
+// upload routes can also be sent json depending on the action requested.
+// handlers take this from the request extensions and apply it before reading
+// json from the body
+#[derive(Debug, Clone, Copy)]
+pub struct JsonBodyLimit(BodyLimitConfig);
+
+impl JsonBodyLimit {
+    pub fn apply(&self, body: Body) -> Body {
+        apply_limit(body, self.0)
+    }
+}
+
+pub struct BodyLimitLayer {
+    limit: BodyLimitConfig,
+    json: Option<JsonBodyLimit>
+}
+
+impl BodyLimitLayer {
+    pub fn new(config: &BodyConfig, kind: BodyKind) -> BodyLimitLayer {
+        match kind {
+            BodyKind::Json => BodyLimitLayer {
+                limit: config.json,
+                json: None
+            },
+            BodyKind::Upload => BodyLimitLayer {
+                limit: config.upload,
+                json: Some(JsonBodyLimit(config.json))
+            }
+        }
+    }
+}
+
+impl<S> Layer<S> for BodyLimitLayer {
+    type Service = BodyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BodyLimitService {
+            inner,
+            limit: self.limit,
+            json: self.json
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BodyLimitService<S> {
+    inner: S,
+    limit: BodyLimitConfig,
+    json: Option<JsonBodyLimit>
+}
+
+impl<S> Service<http::Request> for BodyLimitService<S>
+where
+    S: Service<
+        http::Request,
+        Response = http::Response,
+        Error = http::error::Error,
+        Future = Pin<Box<dyn Future<Output = http::error::Result<http::Response>> + Send>>
+    >
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request) -> Self::Future {
+        let limit = self.limit;
+
+        if let Some(max) = limit.max_size {
+            // no need to wait for the body if we already know it is too big
+            let length = req.headers().get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok());
+
+            if length.map(|length| length > max).unwrap_or(false) {
+                return Box::pin(async move {
+                    Err(Error::from(BodyError::TooLarge))
+                });
+            }
+        }
+
+        if let Some(json) = self.json {
+            req.extensions_mut().insert(json);
+        }
+
+        let (parts, body) = req.into_parts();
+        let body = apply_limit(body, limit);
+
+        self.inner.call(http::Request::from_parts(parts, body))
+    }
+}
\ No newline at end of file
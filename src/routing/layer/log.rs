@@ -69,19 +69,11 @@ where
                     };
                     let mut msg = String::new();
     
-                    if info.remote_addr.is_some() && info.remote_port.is_some() {
-                        let remote_addr = info.remote_addr.unwrap();
-                        let remote_port = info.remote_port.unwrap();
-    
-                        if remote_addr.is_ok() && remote_port.is_ok() {
-                            let addr = remote_addr.unwrap();
-                            let port = remote_port.unwrap();
-    
-                            msg.reserve(addr.len() + 1 + port.len());
-                            msg.push_str(&addr);
-                            msg.push(':');
-                            msg.push_str(&port);
-                        }
+                    if let (Some(Ok(addr)), Some(Ok(port))) = (&info.remote_addr, &info.remote_port) {
+                        msg.reserve(addr.len() + 1 + port.len());
+                        msg.push_str(addr);
+                        msg.push(':');
+                        msg.push_str(port);
                     }
     
                     msg.reserve(
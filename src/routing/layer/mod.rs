@@ -2,5 +2,13 @@ mod log;
 pub use self::log::*;
 mod error;
 pub use self::error::*;
+mod auth;
+pub use self::auth::*;
+mod compression;
+pub use self::compression::*;
+mod cors;
+pub use self::cors::*;
+mod limit;
+pub use self::limit::*;
 // mod state;
 // pub use self::state::*;
\ No newline at end of file
@@ -4,47 +4,31 @@ use std::pin::Pin;
 use std::future::Future;
 use std::task::{Context, Poll};
 use std::result::Result as StdResult;
+use std::sync::Arc;
 
-use hyper::server::conn::AddrStream;
-use hyper::Method;
+use hyper::{Body, Method};
 use hyper::service::Service;
 use tower::ServiceBuilder;
 
 use crate::http::Request;
 use crate::http::Response;
-use crate::http::response::{okay_response, JsonResponseBuilder};
+use crate::http::response::{self, JsonResponseBuilder};
 use crate::http::header::copy_header_value;
 use crate::state::AppState;
+use crate::config::{BodyConfig, CompressionConfig, CorsConfig};
+use crate::net::Connection;
 use crate::http::error::{Error, Result};
 
 mod params;
 pub use params::Params;
-// mod service;
+mod table;
+use table::{RouteTable, Route, Endpoint, HandlerService, ParamKind, handler};
 mod layer;
 mod handle;
 
 #[inline]
-fn method_not_allowed() -> Error {
-    Error::new(405, "MethodNotAllowed", "requested method is not accepted by this resource")
-}
-
-fn join_iter<'a>(iter: &mut impl Iterator<Item = &'a str>) -> String {
-    let mut rtn = String::new();
-    let mut first = true;
-
-    for seg in iter {
-        if first {
-            first = false;
-
-            rtn.push_str(seg);
-        } else {
-            rtn.reserve(seg.len() + 1);
-            rtn.push('/');
-            rtn.push_str(seg)
-        }
-    }
-
-    rtn
+fn not_found() -> Error {
+    Error::new(404, "NotFound", "the requested resource was not found")
 }
 
 #[allow(dead_code)]
@@ -56,130 +40,158 @@ pub fn testing_response(req: Request) -> Result<Response> {
         .response()
 }
 
+fn build_table() -> RouteTable {
+    let mut table = RouteTable::new();
+
+    table.add("/", Route::new()
+        .get(Endpoint::optional(handler!(handle::handle_get))));
+    table.add("/ping", Route::new()
+        .get(Endpoint::public(handler!(handle::ping::handle_get))));
+    table.add("/status", Route::new()
+        .get(Endpoint::admin(handler!(handle::status::handle_get))));
+
+    table.add("/users", Route::new()
+        .get(Endpoint::admin(handler!(handle::users::handle_get)).page())
+        .post(Endpoint::optional(handler!(handle::users::handle_post)))
+        .delete(Endpoint::admin(handler!(handle::users::handle_delete))));
+    table.add("/users/:users_id", Route::new()
+        .param("users_id", ParamKind::Int)
+        .get(Endpoint::session(handler!(handle::users::users_id::handle_get)).page())
+        .put(Endpoint::session(handler!(handle::users::users_id::handle_put)))
+        .delete(Endpoint::admin(handler!(handle::users::users_id::handle_delete))));
+
+    table.add("/invites", Route::new()
+        .get(Endpoint::admin(handler!(handle::invites::handle_get)).page())
+        .post(Endpoint::admin(handler!(handle::invites::handle_post))));
+    table.add("/invites/:invite_id", Route::new()
+        .param("invite_id", ParamKind::Text)
+        .delete(Endpoint::admin(handler!(handle::invites::invites_id::handle_delete))));
+
+    table.add("/quota", Route::new()
+        .get(Endpoint::session(handler!(handle::quota::handle_get)).page()));
+    table.add("/usage", Route::new()
+        .get(Endpoint::session(handler!(handle::usage::handle_get)).page()));
+    table.add("/audit", Route::new()
+        .get(Endpoint::session(handler!(handle::audit::handle_get)).page()));
+    table.add("/register", Route::new()
+        .post(Endpoint::public(handler!(handle::register::handle_post))));
+
+    table.add("/listeners", Route::new()
+        .get(Endpoint::session(handler!(handle::listeners::handle_get)).page())
+        .post(Endpoint::session(handler!(handle::listeners::handle_post)))
+        .delete(Endpoint::session(handler!(handle::listeners::handle_delete))));
+    table.add("/listeners/:listener_id", Route::new()
+        .param("listener_id", ParamKind::Uuid)
+        .get(Endpoint::session(handler!(handle::listeners::listener_id::handle_get)).page())
+        .put(Endpoint::session(handler!(handle::listeners::listener_id::handle_put)))
+        .delete(Endpoint::session(handler!(handle::listeners::listener_id::handle_delete))));
+    table.add("/listeners/:listener_id/deliveries", Route::new()
+        .param("listener_id", ParamKind::Uuid)
+        .get(Endpoint::session(handler!(handle::listeners::listener_id::handle_get_deliveries))));
+    table.add("/listeners/:listener_id/redeliver", Route::new()
+        .param("listener_id", ParamKind::Uuid)
+        .post(Endpoint::session(handler!(handle::listeners::listener_id::handle_post_redeliver))));
+    table.add("/listeners/:listener_id/rotate", Route::new()
+        .param("listener_id", ParamKind::Uuid)
+        .post(Endpoint::session(handler!(handle::listeners::listener_id::handle_post_rotate))));
+
+    table.add("/session", Route::new()
+        .get(Endpoint::session(handler!(handle::session::handle_get)).page())
+        .delete(Endpoint::session(handler!(handle::session::handle_delete))));
+    table.add("/session/:session_id", Route::new()
+        .param("session_id", ParamKind::Uuid)
+        .delete(Endpoint::session(handler!(handle::session::session_id::handle_delete))));
+
+    table.add_tree("/fs", Route::new()
+        .param("context", ParamKind::Path)
+        .get(Endpoint::session(handler!(handle::fs::handle_get)).page())
+        .post(Endpoint::session(handler!(handle::fs::handle_post)).upload())
+        .put(Endpoint::session(handler!(handle::fs::handle_put)).upload())
+        .delete(Endpoint::session(handler!(handle::fs::handle_delete))));
+    table.add_tree("/events/sse", Route::new()
+        .param("context", ParamKind::Path)
+        .get(Endpoint::session(handler!(handle::events::handle_get_sse))));
+    table.add_tree("/events/ws", Route::new()
+        .param("context", ParamKind::Path)
+        .get(Endpoint::session(handler!(handle::events::handle_get_ws))));
+    table.add_tree("/sync", Route::new()
+        .param("context", ParamKind::Path)
+        .put(Endpoint::session(handler!(handle::sync::handle_put))));
+    table.add_tree("/changes", Route::new()
+        .param("context", ParamKind::Path)
+        .get(Endpoint::session(handler!(handle::changes::handle_get))));
+
+    table.add("/auth/session", Route::new()
+        .get(Endpoint::public(handler!(handle::auth::session::handle_get)))
+        .post(Endpoint::public(handler!(handle::auth::session::handle_post)))
+        .delete(Endpoint::public(handler!(handle::auth::session::handle_delete))));
+    table.add("/auth/password", Route::new()
+        .post(Endpoint::session(handler!(handle::auth::password::handle_post))));
+    table.add("/auth/verify", Route::new()
+        .get(Endpoint::public(handler!(handle::auth::verify::handle_get))));
+
+    table
+}
+
 pub struct Router {
     connection: IpAddr,
-    state: AppState
+    state: AppState,
+    table: Arc<RouteTable>,
+    body: BodyConfig
 }
 
 impl Router {
-    async fn handle_route(state: AppState, mut req: Request) -> Result<Response> {
-        let method = req.method().clone();
-        let path = req.uri().path().to_owned();
-        let segments: Vec<&str> = path.strip_prefix("/").unwrap().split("/").collect();
-        let total_segments = segments.len();
-        let mut segments_iter = segments.into_iter();
-
-        if let Some(first_seg) = segments_iter.next() {
-            if first_seg == "" && total_segments == 1 {
-                return match method {
-                    Method::GET => handle::handle_get(req).await,
-                    _ => Err(method_not_allowed())
-                }
-            } else if first_seg == "ping" && total_segments == 1 {
-                return match method {
-                    Method::GET => handle::ping::handle_get(req).await,
-                    _ => Err(method_not_allowed())
-                }
-            } else if first_seg == "users" {
-                if total_segments == 1 {
-                    return match method {
-                        Method::GET => okay_response(req),
-                        Method::POST => handle::users::handle_post(state, req).await,
-                        Method::DELETE => okay_response(req),
-                        _ => Err(method_not_allowed())
-                    }
-                }
+    async fn call_endpoint(state: AppState, body: BodyConfig, endpoint: Endpoint, req: Request) -> Result<Response> {
+        let mut svc = ServiceBuilder::new()
+            .layer(layer::AuthLayer::new(state.clone(), endpoint.auth))
+            .layer(layer::BodyLimitLayer::new(&body, endpoint.body))
+            .service(HandlerService::new(state, endpoint.handler));
 
-                let params = params::Params::with([
-                    ("users_id".into(), segments_iter.next().unwrap().into())
-                ]);
+        svc.call(req).await
+    }
 
-                if total_segments == 2 {
-                    req.extensions_mut().insert(params);
+    async fn handle_route(state: AppState, table: Arc<RouteTable>, body: BodyConfig, mut req: Request) -> Result<Response> {
+        let path = req.uri().path().to_owned();
 
-                    return match method {
-                        Method::GET => okay_response(req),
-                        Method::PUT => okay_response(req),
-                        Method::DELETE => okay_response(req),
-                        _ => Err(method_not_allowed())
-                    }
-                }
-            } else if first_seg == "listeners" {
-                if total_segments == 1 {
-                    return match method {
-                        Method::GET => handle::listeners::handle_get(state, req).await,
-                        Method::POST => handle::listeners::handle_post(state, req).await,
-                        Method::DELETE => handle::listeners::handle_delete(state, req).await,
-                        _ => Err(method_not_allowed())
-                    }
-                }
+        let (route, params) = match table.find(&path)? {
+            Some(found) => found,
+            None => {
+                // only paths outside of the api are left for static files
+                return if table.is_api_path(&path) {
+                    Err(not_found())
+                } else {
+                    handle::_static_::handle_req(state, req).await
+                };
+            }
+        };
+        let method = req.method().clone();
 
-                req.extensions_mut().insert(params::Params::with([
-                    ("listener_id".into(), segments_iter.next().unwrap().into())
-                ]));
-            } else if first_seg == "session" {
-                if total_segments == 1 {
-                    return match method {
-                        Method::GET => handle::session::handle_get(state, req).await,
-                        Method::DELETE => handle::session::handle_delete(state, req).await,
-                        _ => Err(method_not_allowed())
-                    }
-                }
+        req.extensions_mut().insert(params);
 
-                req.extensions_mut().insert(params::Params::with([
-                    ("session_id".into(), segments_iter.next().unwrap().into())
-                ]));
+        if let Some(endpoint) = route.endpoint(&method) {
+            return Self::call_endpoint(state, body, endpoint, req).await;
+        }
 
-                return match method {
-                    Method::DELETE => handle::session::session_id::handle_delete(state, req).await,
-                    _ => Err(method_not_allowed())
-                }
-            } else if first_seg == "fs" {
-                req.extensions_mut().insert(params::Params::with([
-                    ("context".into(), join_iter(&mut segments_iter))
-                ]));
-
-                return match method {
-                    Method::GET => handle::fs::handle_get(state, req).await,
-                    Method::POST => handle::fs::handle_post(state, req).await,
-                    Method::PUT => handle::fs::handle_put(state, req).await,
-                    Method::DELETE => handle::fs::handle_delete(state, req).await,
-                    _ => Err(method_not_allowed())
-                }
-            } else if first_seg == "sync" {
-                req.extensions_mut().insert(params::Params::with([
-                    ("context".into(), join_iter(&mut segments_iter))
-                ]));
-
-                return match method {
-                    Method::PUT => handle::sync::handle_put(state, req).await,
-                    _ => Err(method_not_allowed())
-                }
-            } else if first_seg == "auth" {
-                if let Some(second_seg) = segments_iter.next() {
-                    if second_seg == "session" {
-                        return match method {
-                            Method::GET => handle::auth::session::handle_get(state, req).await,
-                            Method::POST => handle::auth::session::handle_post(state, req).await,
-                            Method::DELETE => handle::auth::session::handle_delete(state, req).await,
-                            _ => Err(method_not_allowed())
-                        }
-                    } else if second_seg == "password" {
-                        return match method {
-                            Method::POST => handle::auth::password::handle_post(state, req).await,
-                            _ => Err(method_not_allowed())
-                        }
-                    }
-                }
-            }
+        if method == Method::HEAD {
+            if let Some(endpoint) = route.endpoint(&Method::GET) {
+                let (parts, _) = Self::call_endpoint(state, body, endpoint, req).await?.into_parts();
 
-            handle::_static_::handle_req(state, req).await
-        } else {
-            match method {
-                Method::GET => handle::handle_get(req).await,
-                _ => Err(method_not_allowed())
+                return Ok(Response::from_parts(parts, Body::empty()));
             }
         }
+
+        if method == Method::OPTIONS {
+            return Ok(response::build()
+                .status(204)
+                .header("allow", route.allow())
+                .body(Body::empty())?);
+        }
+
+        JsonResponseBuilder::new(405)
+            .set_error("MethodNotAllowed")
+            .set_message("requested method is not accepted by this resource")
+            .add_header("allow", route.allow())
+            .response()
     }
 }
 
@@ -193,12 +205,14 @@ impl Service<Request> for Router {
     }
 
     fn call(&mut self, mut req: Request) -> Self::Future {
-        let mut connection = self.connection.clone();
+        let mut connection = self.connection;
         let state = self.state.clone();
+        let table = self.table.clone();
+        let body = self.body;
 
         Box::pin(async move {
             if let Some(ip_header) = copy_header_value(req.headers(), "x-forwarded-for") {
-                let ip_str = ip_header.map_err(|v| Error::from(v))?;
+                let ip_str = ip_header.map_err(Error::from)?;
                 
                 if let Ok(ip) = ip_str.parse() {
                     connection = ip;
@@ -207,21 +221,37 @@ impl Service<Request> for Router {
 
             req.extensions_mut().insert(connection);
 
-            Self::handle_route(state, req).await
+            Self::handle_route(state, table, body, req).await
         })
     }
 }
 
 #[derive(Clone)]
 pub struct MakeRouter {
-    pub state: AppState
+    state: AppState,
+    table: Arc<RouteTable>,
+    compression: CompressionConfig,
+    cors: layer::CorsLayer,
+    body: BodyConfig
+}
+
+impl MakeRouter {
+    pub fn new(state: AppState, compression: CompressionConfig, cors: &CorsConfig, body: BodyConfig) -> MakeRouter {
+        MakeRouter {
+            state,
+            table: Arc::new(build_table()),
+            compression,
+            cors: layer::CorsLayer::new(cors),
+            body
+        }
+    }
 }
 
 // so for how this works. the service works in two steps. the first service 
 // accepts the target of the inbound connection. from there, this service 
 // will return another that will work on any requests from that connection.
-impl<'t> Service<&'t AddrStream> for MakeRouter {
-    type Response = layer::LogService<layer::ErrorService<Router>>;
+impl<'t> Service<&'t Connection> for MakeRouter {
+    type Response = layer::LogService<layer::CorsService<layer::CompressionService<layer::ErrorService<Router>>>>;
     type Error = Infallible;
     type Future = Pin<Box<dyn Future<Output = StdResult<Self::Response, Self::Error>> + Send>>;
 
@@ -229,16 +259,20 @@ impl<'t> Service<&'t AddrStream> for MakeRouter {
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, addr: &'t AddrStream) -> Self::Future {
+    fn call(&mut self, addr: &'t Connection) -> Self::Future {
         let remote_addr = addr.remote_addr();
 
         let router = Router {
             connection: remote_addr.ip(),
-            state: self.state.clone()
+            state: self.state.clone(),
+            table: self.table.clone(),
+            body: self.body
         };
 
         let svc = ServiceBuilder::new()
             .layer(layer::LogLayer::new())
+            .layer(self.cors.clone())
+            .layer(layer::CompressionLayer::new(self.compression.clone()))
             .layer(layer::ErrorLayer::new())
             .service(router);
 
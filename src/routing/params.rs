@@ -5,21 +5,17 @@ pub struct Params(HashMap<String, String>);
 
 impl Params {
 
-    // pub fn with_capacity(size: usize) -> Params {
-    //     Params(HashMap::with_capacity(size))
-    // }
-
-    pub fn with<const N: usize>(list: [(String, String); N]) -> Params {
-        Params (HashMap::from(list))
+    pub fn with_capacity(size: usize) -> Params {
+        Params(HashMap::with_capacity(size))
     }
 
-    // pub fn insert<K,V>(&mut self, key: K, value: V) -> Option<String>
-    // where
-    //     K: Into<String>,
-    //     V: Into<String>
-    // {
-    //     self.0.insert(key.into(), value.into())
-    // }
+    pub fn insert<K,V>(&mut self, key: K, value: V) -> Option<String>
+    where
+        K: Into<String>,
+        V: Into<String>
+    {
+        self.0.insert(key.into(), value.into())
+    }
 
     // pub fn has_key<K>(&self, key: &K) -> bool
     // where
@@ -1,6 +0,0 @@
-mod not_found;
-pub use self::not_found::*;
-mod endpoint;
-pub use self::endpoint::*;
-mod router;
-pub use self::router::*;
\ No newline at end of file
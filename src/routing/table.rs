@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use hyper::Method;
+use tower::Service;
+use uuid::Uuid;
+
+use crate::http::{Request, Response, error::{Error, Result}};
+use crate::state::AppState;
+
+use super::params::Params;
+use super::layer::{Access, AuthRequirement, BodyKind};
+
+pub type HandlerFuture = Pin<Box<dyn Future<Output = Result<Response>> + Send>>;
+pub type Handler = fn(AppState, Request) -> HandlerFuture;
+
+// wraps an async handler fn so that it can be stored in the table
+macro_rules! handler {
+    ($func:path) => {
+        |state: $crate::state::AppState, req: $crate::http::Request| -> $crate::routing::table::HandlerFuture {
+            Box::pin($func(state, req))
+        }
+    };
+}
+
+pub(crate) use handler;
+
+// calls the handler with the app state. this is what the auth layer wraps
+#[derive(Clone)]
+pub struct HandlerService {
+    state: AppState,
+    handler: Handler
+}
+
+impl HandlerService {
+    pub fn new(state: AppState, handler: Handler) -> HandlerService {
+        HandlerService { state, handler }
+    }
+}
+
+impl Service<Request> for HandlerService {
+    type Response = Response;
+    type Error = Error;
+    type Future = HandlerFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        (self.handler)(self.state.clone(), req)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Endpoint {
+    pub handler: Handler,
+    pub auth: AuthRequirement,
+    pub body: BodyKind
+}
+
+impl Endpoint {
+    fn with_access(handler: Handler, access: Access) -> Endpoint {
+        Endpoint {
+            handler,
+            auth: AuthRequirement {
+                access,
+                page: false
+            },
+            body: BodyKind::Json
+        }
+    }
+
+    pub fn public(handler: Handler) -> Endpoint {
+        Self::with_access(handler, Access::Public)
+    }
+
+    pub fn optional(handler: Handler) -> Endpoint {
+        Self::with_access(handler, Access::Optional)
+    }
+
+    pub fn session(handler: Handler) -> Endpoint {
+        Self::with_access(handler, Access::Session)
+    }
+
+    pub fn admin(handler: Handler) -> Endpoint {
+        Self::with_access(handler, Access::Admin)
+    }
+
+    // the endpoint also serves the index page for html requests
+    pub fn page(mut self) -> Endpoint {
+        self.auth.page = true;
+        self
+    }
+
+    // the body is a file upload and uses the upload limits
+    pub fn upload(mut self) -> Endpoint {
+        self.body = BodyKind::Upload;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ParamKind {
+    Int,
+    Uuid,
+    Text,
+    // a catch all param. the leading slash is removed from the value
+    Path
+}
+
+impl ParamKind {
+    fn parse(&self, name: &str, value: &str) -> Result<String> {
+        let valid = match self {
+            ParamKind::Int => value.parse::<i64>().is_ok(),
+            ParamKind::Uuid => value.parse::<Uuid>().is_ok(),
+            ParamKind::Text => !value.is_empty(),
+            ParamKind::Path => {
+                return Ok(value.strip_prefix('/').unwrap_or(value).to_owned());
+            }
+        };
+
+        if valid {
+            Ok(value.to_owned())
+        } else {
+            Err(Error::new(
+                400,
+                "InvalidPathParam",
+                format!("the value given for \"{}\" is not a valid {}", name, self.type_name())
+            ))
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            ParamKind::Int => "integer",
+            ParamKind::Uuid => "uuid",
+            ParamKind::Text => "string",
+            ParamKind::Path => "path"
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Route {
+    params: Vec<(&'static str, ParamKind)>,
+    methods: Vec<(Method, Endpoint)>
+}
+
+impl Route {
+    pub fn new() -> Route {
+        Route {
+            params: Vec::new(),
+            methods: Vec::new()
+        }
+    }
+
+    pub fn param(mut self, name: &'static str, kind: ParamKind) -> Route {
+        self.params.push((name, kind));
+        self
+    }
+
+    pub fn on(mut self, method: Method, endpoint: Endpoint) -> Route {
+        self.methods.push((method, endpoint));
+        self
+    }
+
+    pub fn get(self, endpoint: Endpoint) -> Route {
+        self.on(Method::GET, endpoint)
+    }
+
+    pub fn post(self, endpoint: Endpoint) -> Route {
+        self.on(Method::POST, endpoint)
+    }
+
+    pub fn put(self, endpoint: Endpoint) -> Route {
+        self.on(Method::PUT, endpoint)
+    }
+
+    pub fn delete(self, endpoint: Endpoint) -> Route {
+        self.on(Method::DELETE, endpoint)
+    }
+
+    pub fn endpoint(&self, method: &Method) -> Option<Endpoint> {
+        self.methods.iter()
+            .find(|(m, _)| m == method)
+            .map(|(_, endpoint)| *endpoint)
+    }
+
+    // value for the allow header. HEAD comes along with GET and OPTIONS is
+    // always answered by the router
+    pub fn allow(&self) -> String {
+        let mut list: Vec<&str> = Vec::with_capacity(self.methods.len() + 2);
+
+        for (method, _) in &self.methods {
+            list.push(method.as_str());
+
+            if *method == Method::GET && self.endpoint(&Method::HEAD).is_none() {
+                list.push(Method::HEAD.as_str());
+            }
+        }
+
+        list.push(Method::OPTIONS.as_str());
+        list.join(", ")
+    }
+}
+
+pub struct RouteTable {
+    router: matchit::Router<Route>,
+    // first segments of every route. anything under these that does not
+    // match a route is a 404 instead of being handed to the static handler
+    prefixes: HashSet<String>
+}
+
+impl RouteTable {
+    pub fn new() -> RouteTable {
+        RouteTable {
+            router: matchit::Router::new(),
+            prefixes: HashSet::new()
+        }
+    }
+
+    // the table is built once at startup from a fixed list so a bad route is
+    // a programming error
+    pub fn add(&mut self, path: &str, route: Route) {
+        if let Err(err) = self.router.insert(path, route) {
+            panic!("failed to add route \"{}\". {}", path, err);
+        }
+
+        if let Some(prefix) = first_segment(path) {
+            self.prefixes.insert(prefix.to_owned());
+        }
+    }
+
+    // adds the route for the path itself and for everything under it. the
+    // route is expected to declare a "context" path param
+    pub fn add_tree(&mut self, path: &str, route: Route) {
+        self.add(&format!("{}/*context", path), route.clone());
+        self.add(path, route);
+    }
+
+    pub fn find(&self, path: &str) -> Result<Option<(&Route, Params)>> {
+        let matched = match self.router.at(path) {
+            Ok(matched) => matched,
+            Err(_) => return Ok(None)
+        };
+        let route = matched.value;
+        let mut params = Params::with_capacity(route.params.len());
+
+        // a catch all can also be declared on a route without one, like /fs,
+        // so that handlers always have the param
+        for (name, kind) in &route.params {
+            let value = matched.params.get(name).unwrap_or("");
+
+            params.insert(*name, kind.parse(name, value)?);
+        }
+
+        Ok(Some((route, params)))
+    }
+
+    pub fn is_api_path(&self, path: &str) -> bool {
+        first_segment(path)
+            .map(|prefix| self.prefixes.contains(prefix))
+            .unwrap_or(false)
+    }
+}
+
+fn first_segment(path: &str) -> Option<&str> {
+    path.strip_prefix('/')
+        .and_then(|stripped| stripped.split('/').next())
+        .filter(|seg| !seg.is_empty() && !seg.starts_with(':') && !seg.starts_with('*'))
+}
\ No newline at end of file
@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use futures::{stream::FuturesUnordered, StreamExt};
+use tokio::time::{interval, MissedTickBehavior};
+
+use crate::{
+    config::SchedulerConfig,
+    http::error::Result,
+    state::AppState,
+    db::record::FsItem,
+    components::sync::{sync_item, remove_item, SyncOptions},
+    event
+};
+
+async fn sync_user(state: &AppState, users_id: i64) -> Result<()> {
+    let mut conn = state.db.pool.get().await?;
+
+    let root = match FsItem::find_user_root(&*conn, &users_id).await? {
+        Some(root) => root,
+        None => return Ok(())
+    };
+
+    let transaction = conn.transaction().await?;
+    let result = sync_item(state, &transaction, &root, SyncOptions::default()).await?;
+
+    transaction.commit().await?;
+
+    if result.has_changes() {
+        log::info!(
+            "scheduled sync for user {}. created: {} updated: {} missing: {}",
+            users_id,
+            result.created,
+            result.updated,
+            result.missing
+        );
+
+        state.spawn_tracked(event::trigger_fs_item_synced(
+            state,
+            root,
+            result.created,
+            result.updated,
+            result.missing
+        ));
+    }
+
+    Ok(())
+}
+
+// syncs every user root, keeping at most concurrency of them running at once
+async fn sync_all(state: &AppState, concurrency: usize) -> Result<()> {
+    let users: Vec<i64> = {
+        let conn = state.db.pool.get().await?;
+
+        conn.query("select users_id from fs_items where is_root = true", &[])
+            .await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect()
+    };
+    let mut users = users.into_iter();
+    let mut running = FuturesUnordered::new();
+
+    loop {
+        while running.len() < concurrency {
+            if let Some(users_id) = users.next() {
+                running.push(async move {
+                    (users_id, sync_user(state, users_id).await)
+                });
+            } else {
+                break;
+            }
+        }
+
+        if let Some((users_id, result)) = running.next().await {
+            if let Err(err) = result {
+                log::error!("scheduled sync failed for user {}. {}", users_id, err);
+            }
+        } else {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// removes records that have been missing for longer than the retention
+// period. only the top most missing item of a tree is selected since
+// everything under it is removed with it
+async fn purge_missing(state: &AppState, purge_after: u64) -> Result<u64> {
+    let cutoff = Utc::now() - chrono::Duration::seconds(purge_after as i64);
+    let ids: Vec<i64> = {
+        let conn = state.db.pool.get().await?;
+
+        conn.query(
+            "\
+            select fs_items.id \
+            from fs_items \
+            left join fs_items fs_parent on fs_parent.id = fs_items.parent \
+            where fs_items.item_exists = false and \
+                  fs_items.is_root = false and \
+                  fs_items.missing_since < $1 and \
+                  not coalesce( \
+                      fs_parent.item_exists = false and \
+                      fs_parent.missing_since < $1 and \
+                      fs_parent.is_root = false, \
+                      false \
+                  )",
+            &[&cutoff]
+        ).await?
+            .iter()
+            .map(|row| row.get(0))
+            .collect()
+    };
+    let mut purged = 0;
+
+    for id in ids {
+        let item = {
+            let conn = state.db.pool.get().await?;
+
+            FsItem::find_id(&*conn, &id).await?
+        };
+
+        // the item could have been found again since it was selected
+        if let Some(item) = item {
+            if !item.item_exists {
+                remove_item(state, item).await?;
+                purged += 1;
+            }
+        }
+    }
+
+    Ok(purged)
+}
+
+pub async fn run(state: AppState, conf: SchedulerConfig) {
+    let mut timer = interval(Duration::from_secs(conf.sync_interval));
+    timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // the first tick completes immediately. skip it so that startup is not
+    // slowed down by a full sync
+    timer.tick().await;
+
+    loop {
+        // a sync that has started is allowed to finish
+        tokio::select! {
+            _ = timer.tick() => {},
+            _ = state.shutdown.wait() => break
+        }
+
+        log::info!("running scheduled sync");
+
+        if let Err(err) = sync_all(&state, conf.concurrency).await {
+            log::error!("scheduled sync error. {}", err);
+        }
+
+        if let Some(purge_after) = conf.purge_after {
+            match purge_missing(&state, purge_after).await {
+                Ok(count) => {
+                    if count > 0 {
+                        log::info!("purged {} missing items", count);
+                    }
+                },
+                Err(err) => {
+                    log::error!("failed to purge missing items. {}", err);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -4,6 +4,20 @@ pub fn rand_bytes(size: usize) -> Option<Vec<u8>> {
     let mut rtn: Vec<u8> = vec!(0; size);
 
     if let Ok(()) = SystemRandom::new().fill(rtn.as_mut_slice()) {
+        Some(rtn)
+    } else {
+        None
+    }
+}
+
+pub fn rand_hex(size: usize) -> Option<String> {
+    if let Some(bytes) = rand_bytes(size) {
+        let mut rtn = String::with_capacity(size * 2);
+
+        for byte in bytes {
+            rtn.push_str(&format!("{:02x}", byte));
+        }
+
         Some(rtn)
     } else {
         None
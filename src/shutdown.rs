@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::{watch, Notify};
+
+use crate::error;
+
+// tracks when the server has been asked to stop along with background tasks
+// that should be allowed to finish before it does
+pub struct ShutdownState {
+    sender: watch::Sender<bool>,
+    receiver: watch::Receiver<bool>,
+    tasks: AtomicUsize,
+    idle: Notify
+}
+
+pub type ArcShutdownState = Arc<ShutdownState>;
+
+// held by a tracked task until it finishes
+pub struct TaskGuard {
+    state: ArcShutdownState
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if self.state.tasks.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.state.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownState {
+    pub fn new() -> ArcShutdownState {
+        let (sender, receiver) = watch::channel(false);
+
+        Arc::new(ShutdownState {
+            sender,
+            receiver,
+            tasks: AtomicUsize::new(0),
+            idle: Notify::new()
+        })
+    }
+
+    pub fn trigger(&self) {
+        let _ = self.sender.send(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    // resolves once shutdown has been triggered
+    pub async fn wait(&self) {
+        let mut receiver = self.receiver.clone();
+
+        while !*receiver.borrow() {
+            if receiver.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+
+    pub fn track(self: &Arc<Self>) -> TaskGuard {
+        self.tasks.fetch_add(1, Ordering::AcqRel);
+
+        TaskGuard {
+            state: self.clone()
+        }
+    }
+
+    // resolves once there are no tracked tasks running
+    pub async fn drained(&self) {
+        loop {
+            let notified = self.idle.notified();
+
+            if self.tasks.load(Ordering::Acquire) == 0 {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+pub async fn wait_for_signal() -> error::Result<()> {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+
+    tokio::select! {
+        _ = terminate.recv() => log::info!("received SIGTERM"),
+        _ = interrupt.recv() => log::info!("received SIGINT")
+    }
+
+    Ok(())
+}
\ No newline at end of file
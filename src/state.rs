@@ -1,16 +1,33 @@
-use crate::{db::ArcDBState, template::ArcTemplateState, snowflakes::IdSnowflakes, storage::ArcStorageState, http::Request};
+use std::future::Future;
+
+use crate::{db::ArcDBState, template::ArcTemplateState, snowflakes::IdSnowflakes, storage::ArcStorageState, email::ArcEmailState, event::ArcEventState, net::ArcConnectionState, shutdown::ArcShutdownState};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: ArcDBState,
     pub storage: ArcStorageState,
     pub template: ArcTemplateState<'static>,
+    pub email: ArcEmailState,
+    pub events: ArcEventState,
     pub snowflakes: IdSnowflakes,
     pub offload: tokio::runtime::Handle,
+    pub connections: ArcConnectionState,
+    pub shutdown: ArcShutdownState,
 }
 
 impl AppState {
-    pub fn from(req: &mut Request) -> AppState {
-        req.extensions_mut().remove().unwrap()
+    // spawns onto the offload runtime. shutdown will wait for the task to
+    // finish before the server exits
+    pub fn spawn_tracked<F>(&self, fut: F)
+    where
+        F: Future<Output = ()> + Send + 'static
+    {
+        let guard = self.shutdown.track();
+
+        self.offload.spawn(async move {
+            fut.await;
+
+            drop(guard);
+        });
     }
 }
\ No newline at end of file
@@ -1,2 +1,4 @@
 mod shared_state;
-pub use shared_state::*;
\ No newline at end of file
+pub use shared_state::*;
+mod writes;
+pub use writes::*;
\ No newline at end of file
@@ -4,6 +4,8 @@ use std::path::PathBuf;
 
 use crate::config::StorageConfig;
 
+use super::WriteTracker;
+
 pub struct StaticResources {
     pub directories: HashMap<String, PathBuf>,
     pub files: HashMap<String, PathBuf>
@@ -13,34 +15,25 @@ pub struct StorageState {
     pub directory: PathBuf,
     pub temporary: PathBuf,
     pub web_static: Option<PathBuf>,
+
+    // default quota in bytes for users that do not have one set
+    pub quota: Option<u64>,
     
-    pub static_resources: StaticResources
+    pub static_resources: StaticResources,
+
+    pub writes: WriteTracker
 }
 
 pub type ArcStorageState = Arc<StorageState>;
 
 impl StorageState {
 
-    pub fn get_tmp_file(&self, ext: &str) -> Option<PathBuf> {
-        let mut count: u64 = 0;
-        let mut tmp_file = self.temporary.clone();
-        let now = chrono::Utc::now().timestamp().to_string();
-
-        loop {
-            let file_name = format!("{}_{}", now, count);
-            tmp_file.set_file_name(file_name);
-            tmp_file.set_extension(ext);
-
-            if !tmp_file.exists() {
-                return Some(tmp_file)
-            } else {
-                if count == u64::MAX {
-                    return None;
-                } else {
-                    count += 1;
-                }
-            }
-        }
+    // files in the temporary directory are never seen by the watcher or sync
+    // so the name only has to be unique
+    pub fn get_tmp_file(&self, ext: &str) -> PathBuf {
+        let mut tmp_file = self.temporary.join(uuid::Uuid::new_v4().to_simple().to_string());
+        tmp_file.set_extension(ext);
+        tmp_file
     }
 }
 
@@ -50,10 +43,12 @@ impl From<StorageConfig> for ArcStorageState {
             directory: storage.directory,
             temporary: storage.temporary,
             web_static: storage.web_static,
+            quota: storage.quota,
             static_resources: StaticResources {
                 directories: storage.static_.directories,
                 files: storage.static_.files
-            }
+            },
+            writes: WriteTracker::default()
         })
     }
 }
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// the watcher can get events for a path a few seconds after the handler is
+// done with it so paths are still skipped for a while after being released
+const RELEASE_GRACE: Duration = Duration::from_secs(15);
+
+struct Entry {
+    active: usize,
+    // everything under the path is skipped as well
+    recursive: bool,
+    released: Option<Instant>
+}
+
+// paths that request handlers are changing in storage. the handler keeps the
+// records up to date itself so the watcher skips events for these
+#[derive(Clone, Default)]
+pub struct WriteTracker {
+    paths: Arc<Mutex<HashMap<PathBuf, Entry>>>
+}
+
+impl WriteTracker {
+    fn add(&self, path: PathBuf, recursive: bool) -> WriteGuard {
+        let mut paths = self.paths.lock().unwrap();
+        let entry = paths.entry(path.clone()).or_insert(Entry {
+            active: 0,
+            recursive: false,
+            released: None
+        });
+
+        entry.active += 1;
+        entry.recursive = entry.recursive || recursive;
+        entry.released = None;
+
+        WriteGuard {
+            tracker: self.clone(),
+            path
+        }
+    }
+
+    pub fn track(&self, path: PathBuf) -> WriteGuard {
+        self.add(path, false)
+    }
+
+    // for directories that are moved or removed along with their contents
+    pub fn track_tree(&self, path: PathBuf) -> WriteGuard {
+        self.add(path, true)
+    }
+
+    pub fn is_tracked(&self, path: &Path) -> bool {
+        let now = Instant::now();
+        let mut paths = self.paths.lock().unwrap();
+
+        paths.retain(|_, entry| {
+            entry.active > 0 || entry.released
+                .map(|released| now.duration_since(released) < RELEASE_GRACE)
+                .unwrap_or(true)
+        });
+
+        if paths.contains_key(path) {
+            return true;
+        }
+
+        path.ancestors()
+            .skip(1)
+            .any(|parent| paths.get(parent).map(|entry| entry.recursive).unwrap_or(false))
+    }
+}
+
+pub struct WriteGuard {
+    tracker: WriteTracker,
+    path: PathBuf
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        let mut paths = self.tracker.paths.lock().unwrap();
+
+        if let Some(entry) = paths.get_mut(&self.path) {
+            entry.active -= 1;
+
+            if entry.active == 0 {
+                entry.released = Some(Instant::now());
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,6 +1,5 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
 use handlebars::{Handlebars, RenderContext, Helper, Context, HelperResult, Output, RenderError};
-use lib;
 
 pub fn join_to_path(h: &Helper, _: &Handlebars, _: &Context, _: &mut RenderContext, out: &mut dyn Output) -> HelperResult {
     for param in h.params() {
@@ -12,13 +12,13 @@ mod helpers;
 
 pub use shared_state::*;
 
-fn recursive_load_directory<'a>(
+fn recursive_load_directory(
     config: &TemplateConfig,
     hb: &mut Handlebars,
     directory: &PathBuf,
     template_errors: &mut Vec<TemplateError>,
 ) -> error::Result<()> {
-    for item in read_dir(&directory)? {
+    for item in read_dir(directory)? {
         let file = item?;
         let path = file.path();
 
@@ -80,7 +80,7 @@ pub fn build_registry<'a>(config: TemplateConfig) -> error::Result<Handlebars<'a
         }
     }
 
-    if template_errors.len() > 0 {
+    if !template_errors.is_empty() {
         let mut msg = "there were errors when attempting to load templates:\n".to_owned();
 
         for err in template_errors {
@@ -0,0 +1,99 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_rustls::{TlsAcceptor, rustls};
+
+use crate::{error, config::SslConfig};
+
+fn load_certs(path: &Path) -> error::Result<Vec<rustls::Certificate>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader)?;
+
+    if certs.is_empty() {
+        return Err(error::Error::Error(format!("no certificates found in \"{}\"", path.display())));
+    }
+
+    Ok(certs.into_iter().map(rustls::Certificate).collect())
+}
+
+fn load_key(path: &Path) -> error::Result<rustls::PrivateKey> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    // the first key found is used
+    for item in rustls_pemfile::read_all(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key) |
+            rustls_pemfile::Item::RSAKey(key) |
+            rustls_pemfile::Item::ECKey(key) => return Ok(rustls::PrivateKey(key)),
+            _ => {}
+        }
+    }
+
+    Err(error::Error::Error(format!("no private key found in \"{}\"", path.display())))
+}
+
+fn load_config(cert: &Path, key: &Path) -> error::Result<Arc<rustls::ServerConfig>> {
+    let mut config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(load_certs(cert)?, load_key(key)?)?;
+
+    // hyper will pick the protocol based on the connection preface so both
+    // can be offered
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(config))
+}
+
+// holds the current server config. connections that are already open keep
+// the config they were accepted with so a reload does not drop them
+#[derive(Clone)]
+pub struct TlsState {
+    cert: PathBuf,
+    key: PathBuf,
+    config: Arc<RwLock<Arc<rustls::ServerConfig>>>
+}
+
+impl TlsState {
+    pub fn new(conf: &SslConfig) -> error::Result<TlsState> {
+        let (cert, key) = match (conf.cert.as_ref(), conf.key.as_ref()) {
+            (Some(cert), Some(key)) => (cert.clone(), key.clone()),
+            _ => return Err(error::Error::Error("conf.ssl.cert and conf.ssl.key are required for tls".to_owned()))
+        };
+        let config = load_config(&cert, &key)?;
+
+        Ok(TlsState {
+            cert,
+            key,
+            config: Arc::new(RwLock::new(config))
+        })
+    }
+
+    pub fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(self.config.read().unwrap().clone())
+    }
+
+    pub fn reload(&self) -> error::Result<()> {
+        let config = load_config(&self.cert, &self.key)?;
+
+        *self.config.write().unwrap() = config;
+
+        Ok(())
+    }
+}
+
+pub async fn reload_on_hangup(tls: TlsState) -> error::Result<()> {
+    let mut hangup = signal(SignalKind::hangup())?;
+
+    while hangup.recv().await.is_some() {
+        match tls.reload() {
+            Ok(()) => log::info!("reloaded tls certificates"),
+            Err(err) => log::error!("failed to reload tls certificates. keeping the current ones. {}", err)
+        }
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -1,21 +1,39 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use notify::{
-    Watcher, 
+    Watcher,
     RecommendedWatcher,
     RecursiveMode,
     EventKind,
     Event,
-    event,
+    event::{CreateKind, ModifyKind, RemoveKind, RenameMode},
     // ErrorKind,
 };
-use tokio::runtime::Handle;
+use tokio::fs::metadata;
 use tokio::sync::mpsc;
+use tokio::time::{sleep_until, Instant};
+use tokio_postgres::GenericClient;
 
-use crate::error;
+use crate::{
+    error,
+    http::error::Result,
+    state::AppState,
+    db::record::{FsItem, FsItemType, join_path},
+    storage::WriteTracker,
+    components::sync::{get_directory_and_basename, sync_known_file, sync_file, sync_dir, remove_item},
+    event
+};
+
+// how long a path has to be quiet before the changes are applied
+const DEBOUNCE: Duration = Duration::from_millis(500);
 
-fn watch_directories(watcher: &mut RecommendedWatcher, path: PathBuf) -> error::Result<u32> {
-    let entries = std::fs::read_dir(&path)?;
+// the longest that changes will wait if events keep coming in
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+fn watch_directories(watcher: &mut RecommendedWatcher, path: &Path) -> error::Result<u32> {
+    let entries = std::fs::read_dir(path)?;
     let mut rtn: u32 = 0;
 
     for ent in entries {
@@ -23,94 +41,367 @@ fn watch_directories(watcher: &mut RecommendedWatcher, path: PathBuf) -> error::
         let ent_path = ent.path();
 
         if ent_path.is_dir() {
-            rtn += watch_directories(watcher, ent_path)?
+            rtn += watch_directories(watcher, &ent_path)?
         }
     }
 
-    watcher.watch(&path, RecursiveMode::NonRecursive)?;
+    watcher.watch(path, RecursiveMode::NonRecursive)?;
     rtn += 1;
 
     Ok(rtn)
 }
 
-fn handle_event(watcher: &mut RecommendedWatcher, evt: Event) -> error::Result<()> {
-    println!("new watcher event: {:?}", evt);
+// changes collected during the debounce window. renames are kept separate so
+// that the record can be moved instead of being removed and created again
+#[derive(Default)]
+struct Pending {
+    renames: Vec<(PathBuf, PathBuf)>,
+    paths: HashSet<PathBuf>
+}
+
+impl Pending {
+    fn is_empty(&self) -> bool {
+        self.renames.is_empty() && self.paths.is_empty()
+    }
+}
 
-    match evt.kind {
-        EventKind::Create(create_kind) => match create_kind {
-            event::CreateKind::Folder => {
-                println!("adding folders to watch. count: {}", evt.paths.len());
+fn handle_event(watcher: &mut RecommendedWatcher, writes: &WriteTracker, pending: &mut Pending, evt: Event) {
+    log::debug!("new watcher event: {:?}", evt);
 
-                for path in evt.paths {
-                    match watcher.watch(&path, RecursiveMode::NonRecursive) {
-                        Ok(()) => {},
-                        Err(e) => {
-                            println!("failed to add folder to watcher.\npath: \"{}\"\nerror: {:?}", path.display(), e)
-                        }
-                    }
+    match &evt.kind {
+        EventKind::Create(CreateKind::Folder) => {
+            for path in evt.paths.iter() {
+                if let Err(e) = watch_directories(watcher, path) {
+                    log::error!("failed to add folder to watcher. path: \"{}\" error: {}", path.display(), e);
                 }
+            }
+        },
+        EventKind::Remove(RemoveKind::Folder) => {
+            for path in evt.paths.iter() {
+                if let Err(e) = watcher.unwatch(path) {
+                    log::debug!("error when unwatching path. path: \"{}\" error: {:?}", path.display(), e);
+                }
+            }
+        },
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if evt.paths.len() == 2 => {
+            let from = evt.paths[0].clone();
+            let to = evt.paths[1].clone();
 
-                println!("finished adding directories");
-            },
-            _ => {}
+            if to.is_dir() {
+                let _ = watcher.unwatch(&from);
+
+                if let Err(e) = watch_directories(watcher, &to) {
+                    log::error!("failed to add folder to watcher. path: \"{}\" error: {}", to.display(), e);
+                }
+            }
+
+            if !writes.is_tracked(&from) && !writes.is_tracked(&to) {
+                pending.renames.push((from, to));
+            }
         },
-        EventKind::Remove(remove_kind) => match remove_kind {
-            event::RemoveKind::Folder => {
-                println!("removing folders to watch. count: {}", evt.paths.len());
-
-                for path in evt.paths {
-                    match watcher.unwatch(&path) {
-                        Ok(()) => {},
-                        Err(e) => println!("error when unwatching path.\npath: \"{}\"\nerror: {:?}", path.display(), e)
-                    }
+        _ => {}
+    }
+
+    for path in evt.paths {
+        // changes made by request handlers are already in the database
+        if !writes.is_tracked(&path) {
+            pending.paths.insert(path);
+        }
+    }
+}
+
+// resolves the owner of the path from the name of the root directory. paths
+// for the storage directory or the root directories themselves are ignored
+fn resolve_owner(state: &AppState, path: &Path) -> Result<Option<(i64, String, String)>> {
+    let (directory, basename) = get_directory_and_basename(state, path)?;
+
+    if directory.is_empty() {
+        return Ok(None);
+    }
+
+    let root_name = directory.split('/').next().unwrap();
+
+    if let Ok(users_id) = root_name.parse() {
+        Ok(Some((users_id, directory, basename)))
+    } else {
+        log::warn!("unknown root directory in storage. path: \"{}\"", path.display());
+
+        Ok(None)
+    }
+}
+
+async fn find_parent(conn: &impl GenericClient, users_id: &i64, directory: &str) -> Result<Option<FsItem>> {
+    let (parent_directory, parent_basename) = lib::string::get_directory_and_basename(directory, true);
+
+    FsItem::find_user_id_directory_basename(conn, users_id, &parent_directory, &parent_basename).await
+}
+
+// moves the record and any of its contents to the new location. if the move
+// is not possible the paths will be handled as a remove and a create instead
+async fn apply_rename(state: &AppState, from: &Path, to: &Path) -> Result<()> {
+    let (from_owner, to_owner) = (resolve_owner(state, from)?, resolve_owner(state, to)?);
+
+    let ((users_id, from_directory, from_basename), (to_users_id, to_directory, to_basename)) = match (from_owner, to_owner) {
+        (Some(f), Some(t)) => (f, t),
+        _ => return Ok(())
+    };
+
+    // moving between users would change the owner of everything in the
+    // directory so it is left to the remove and create
+    if users_id != to_users_id {
+        return Ok(());
+    }
+
+    let mut conn = state.db.pool.get().await?;
+    let transaction = conn.transaction().await?;
+
+    let mut item = match FsItem::find_user_id_directory_basename(&transaction, &users_id, &from_directory, &from_basename).await? {
+        Some(item) => item,
+        None => return Ok(())
+    };
+    let parent = match find_parent(&transaction, &users_id, &to_directory).await? {
+        Some(parent) => parent,
+        None => return Ok(())
+    };
+
+    if FsItem::find_user_id_directory_basename(&transaction, &users_id, &to_directory, &to_basename).await?.is_some() {
+        return Ok(());
+    }
+
+    transaction.execute(
+        "\
+        update fs_items \
+        set parent = $2, \
+            directory = $3, \
+            basename = $4 \
+        where id = $1",
+        &[&item.id, &parent.id, &to_directory, &to_basename]
+    ).await?;
+
+    if item.item_type == FsItemType::Dir {
+        let old_prefix = join_path(&from_directory, &from_basename);
+        let new_prefix = join_path(&to_directory, &to_basename);
+
+        transaction.execute(
+            "\
+            update fs_items \
+            set directory = $3 || substr(directory, length($2) + 1) \
+            where users_id = $1 and \
+                  (directory = $2 or left(directory, length($2) + 1) = $2 || '/')",
+            &[&users_id, &old_prefix, &new_prefix]
+        ).await?;
+    }
+
+    transaction.commit().await?;
+
+    item.parent = Some(parent.id);
+    item.directory = to_directory;
+    item.basename = to_basename;
+
+    state.spawn_tracked(event::trigger_fs_item_updated(state, item));
+
+    Ok(())
+}
+
+// inserts records for anything inside of a newly found directory
+async fn create_contents(state: &AppState, conn: &impl GenericClient, users_id: &i64, dir_id: i64, dir_path: &Path) -> Result<Vec<i64>> {
+    let mut created = Vec::new();
+    let mut working_queue = vec![(dir_id, dir_path.to_path_buf())];
+
+    while let Some((parent, path)) = working_queue.pop() {
+        let mut entries = tokio::fs::read_dir(&path).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let entry_path = entry.path();
+
+            if entry_path.is_dir() {
+                let (id, was_created, _) = sync_dir(state, conn, users_id, &parent, &entry_path).await?;
+
+                if was_created {
+                    created.push(id);
                 }
 
-                println!("finished removing directories");
-            },
-            _ => {}
+                working_queue.push((id, entry_path));
+            } else if entry_path.is_file() {
+                let (id, was_created, _) = sync_file(state, conn, users_id, &parent, &entry_path, false).await?;
+
+                if was_created {
+                    created.push(id);
+                }
+            }
+        }
+    }
+
+    Ok(created)
+}
+
+// compares the current state of the path on disk to the database and applies
+// the differences
+async fn reconcile_path(state: &AppState, path: &Path) -> Result<()> {
+    let (users_id, directory, basename) = match resolve_owner(state, path)? {
+        Some(owner) => owner,
+        None => return Ok(())
+    };
+
+    let conn = state.db.pool.get().await?;
+    let mut existing = FsItem::find_user_id_directory_basename(&*conn, &users_id, &directory, &basename).await?;
+
+    let md = match metadata(path).await {
+        Ok(md) => md,
+        Err(err) => {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                return Err(err.into());
+            }
+
+            if let Some(item) = existing {
+                if item.is_root {
+                    log::warn!("root directory was removed from storage. users_id: {}", users_id);
+                } else {
+                    remove_item(state, item).await?;
+                }
+            }
+
+            return Ok(());
+        }
+    };
+
+    let is_dir = md.is_dir();
+
+    if let Some(item) = existing.take() {
+        if (is_dir && item.item_type != FsItemType::Dir) || (!is_dir && item.item_type != FsItemType::File) {
+            // the type changed so the old record is no longer valid and a new
+            // one is created below
+            remove_item(state, item).await?;
+        } else {
+            existing = Some(item);
+        }
+    }
+
+    if let Some(item) = existing {
+        let updated = if is_dir {
+            if !item.item_exists {
+                FsItem::update_item_exists(&*conn, &item.id, true).await?;
+                true
+            } else {
+                false
+            }
+        } else {
+            sync_known_file(&*conn, path, &md, &item, false).await?.is_some()
+        };
+
+        if updated {
+            if let Some(record) = FsItem::find_id(&*conn, &item.id).await? {
+                state.spawn_tracked(event::trigger_fs_item_updated(state, record));
+            }
+        }
+
+        return Ok(());
+    }
+
+    let parent = match find_parent(&*conn, &users_id, &directory).await? {
+        Some(parent) => parent,
+        None => {
+            log::warn!("parent directory record not found for watched path. path: \"{}\"", path.display());
+            return Ok(());
+        }
+    };
+
+    let mut created = Vec::new();
+    let mut conn = conn;
+    let transaction = conn.transaction().await?;
+    let path_buf = path.to_path_buf();
+
+    if is_dir {
+        let (id, _, _) = sync_dir(state, &transaction, &users_id, &parent.id, &path_buf).await?;
+        created.push(id);
+
+        // anything moved in with the directory will not have its own events
+        created.extend(create_contents(state, &transaction, &users_id, id, path).await?);
+    } else if md.is_file() {
+        let (id, _, _) = sync_file(state, &transaction, &users_id, &parent.id, &path_buf, false).await?;
+        created.push(id);
+    }
+
+    transaction.commit().await?;
+
+    for id in created {
+        if let Some(record) = FsItem::find_id(&*conn, &id).await? {
+            state.spawn_tracked(event::trigger_fs_item_created(state, record));
         }
-        _ => {}
     }
 
     Ok(())
 }
 
+async fn flush(state: &AppState, pending: Pending) {
+    for (from, to) in pending.renames {
+        if let Err(e) = apply_rename(state, &from, &to).await {
+            log::error!("failed to apply rename. from: \"{}\" to: \"{}\" error: {}", from.display(), to.display(), e);
+        }
+    }
+
+    let mut paths: Vec<PathBuf> = pending.paths.into_iter().collect();
+
+    // parents need to exist before their contents can be created
+    paths.sort_by_key(|p| p.components().count());
+
+    for path in paths {
+        if let Err(e) = reconcile_path(state, &path).await {
+            log::error!("failed to sync watched path. path: \"{}\" error: {}", path.display(), e);
+        }
+    }
+}
+
 /**
- * we can use a handle from a tokio runtime to spawn tasks.
- * since the watcher creates its own thread to handle everything
- * the context is not available to use there so the handle will
- * be given instead since it is just a reference counter.
+ * the watcher creates its own thread to handle everything so events are
+ * sent back to the runtime over a channel. changes are collected until
+ * the paths stop changing and then applied to the database.
  */
-pub async fn watch(path: PathBuf) -> error::Result<()> {
-    let (tx, mut rx) = mpsc::channel(24);
-    let rt = Handle::current();
+pub async fn watch(state: AppState) -> error::Result<()> {
+    let (tx, mut rx) = mpsc::channel(1024);
 
     let mut watcher = RecommendedWatcher::new(move |evt| {
-        // from the tokio guides, we need to spawn a new sender
-        // that can be given to the task since it is a one
-        // time use
-        let tx2 = tx.clone();
-
-        rt.spawn(async move {
-            if let Err(e) = tx2.try_send(evt) {
-                match e {
-                    mpsc::error::TrySendError::Full(_v) => {
-                        println!("sender queue is full");
+        // this is called on the watcher thread so it is safe to block
+        if tx.blocking_send(evt).is_err() {
+            log::error!("watcher channel is closed");
+        }
+    })?;
+    let total = watch_directories(&mut watcher, &state.storage.directory)?;
+    log::info!("total directories watched: {}", total);
+
+    let mut pending = Pending::default();
+    let mut first_event = Instant::now();
+    let mut deadline = Instant::now();
+
+    loop {
+        tokio::select! {
+            res = rx.recv() => {
+                match res {
+                    Some(Ok(evt)) => {
+                        let now = Instant::now();
+
+                        if pending.is_empty() {
+                            first_event = now;
+                        }
+
+                        handle_event(&mut watcher, &state.storage.writes, &mut pending, evt);
+
+                        deadline = std::cmp::min(now + DEBOUNCE, first_event + MAX_DELAY);
                     },
-                    mpsc::error::TrySendError::Closed(_v) => {
-                        println!("sender is closed");
-                    }
+                    Some(Err(e)) => log::error!("watch error: {:?}", e),
+                    None => break
+                }
+            },
+            _ = sleep_until(deadline), if !pending.is_empty() => {
+                flush(&state, std::mem::take(&mut pending)).await;
+            },
+            _ = state.shutdown.wait() => {
+                if !pending.is_empty() {
+                    flush(&state, std::mem::take(&mut pending)).await;
                 }
-            }
-        });
-    })?;
-    let total = watch_directories(&mut watcher, path)?;
-    println!("total directories watched: {}", total);
 
-    while let Some(res) = rx.recv().await {
-        match res {
-            Ok(event) => handle_event(&mut watcher, event)?,
-            Err(e) => println!("watch error: {:?}", e)
+                break;
+            }
         }
     }
 